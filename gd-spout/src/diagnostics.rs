@@ -0,0 +1,309 @@
+use godot::prelude::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::settings;
+use crate::settings::LogVerbosity;
+
+// The same diagnostic is logged at most once per interval for every endpoint, repeats are counted in between.
+const RATE_LIMIT: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    NoTexture = 1,
+    RenderingDeviceUnavailable,
+    NativeDeviceUnavailable,
+    InvalidTexture,
+    UnsupportedFormat,
+    SenderNameRejected,
+    BackendSelected,
+    BackendFailed,
+    BackendUnavailable,
+    ConversionFailed,
+    ReadbackFailed,
+    PublishFailed,
+    InvalidFrame,
+    InvalidArgument,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Verbose,
+    Warning,
+    Error,
+}
+
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub code: ErrorCode,
+    pub severity: Severity,
+    pub message: String,
+    // How often the diagnostic was suppressed since it was last logged.
+    pub repeated: u32,
+}
+
+impl Diagnostic {
+    pub fn to_dictionary(&self) -> Dictionary {
+        dict! {
+            "code": self.code as i64,
+            "name": format!("{:?}", self.code),
+            "severity": self.severity as i64,
+            "message": self.message.as_str(),
+            "repeated": self.repeated as i64,
+        }
+    }
+
+    fn is_logged(&self) -> bool {
+        match settings::get().log_verbosity {
+            LogVerbosity::Errors => self.severity >= Severity::Error,
+            LogVerbosity::Warnings => self.severity >= Severity::Warning,
+            LogVerbosity::Verbose => true,
+        }
+    }
+}
+
+struct LogRecord {
+    logged_at: Instant,
+    suppressed: u32,
+}
+
+#[derive(Default)]
+struct DiagnosticsState {
+    last_error: Option<Diagnostic>,
+    records: HashMap<(ErrorCode, String), LogRecord>,
+    pending: Vec<Diagnostic>,
+}
+
+// Collects the diagnostics of one endpoint from any thread. They are logged on the main thread by the manager, which
+// hands them to the log sink of the endpoint.
+#[derive(Clone, Default)]
+pub struct Diagnostics {
+    state: Arc<Mutex<DiagnosticsState>>,
+}
+
+impl Diagnostics {
+    pub fn report(&self, code: ErrorCode, severity: Severity, message: impl Into<String>) {
+        self.report_at(Instant::now(), code, severity, message.into());
+    }
+
+    fn report_at(&self, now: Instant, code: ErrorCode, severity: Severity, message: String) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+
+        let mut diagnostic = Diagnostic {
+            code,
+            severity,
+            message,
+            repeated: 0,
+        };
+
+        if severity >= Severity::Warning {
+            state.last_error = Some(diagnostic.clone());
+        }
+
+        let key = (code, diagnostic.message.clone());
+
+        if let Some(record) = state.records.get_mut(&key) {
+            if now.duration_since(record.logged_at) < RATE_LIMIT {
+                record.suppressed += 1;
+                return;
+            }
+
+            diagnostic.repeated = std::mem::take(&mut record.suppressed);
+            record.logged_at = now;
+        } else {
+            // Messages often contain names or sizes, so records that can no longer suppress anything are dropped
+            // before they pile up. Their suppressed repeats are lost with them.
+            state
+                .records
+                .retain(|_, record| now.duration_since(record.logged_at) < RATE_LIMIT);
+
+            state.records.insert(
+                key,
+                LogRecord {
+                    logged_at: now,
+                    suppressed: 0,
+                },
+            );
+        }
+
+        state.pending.push(diagnostic);
+    }
+
+    pub fn last_error(&self) -> Option<Diagnostic> {
+        self.state.lock().ok().and_then(|state| state.last_error.clone())
+    }
+
+    // What endpoints return from `get_last_error()`, empty when nothing went wrong since they started or the error was
+    // cleared.
+    pub fn last_error_dictionary(&self) -> Dictionary {
+        self.last_error()
+            .map(|diagnostic| diagnostic.to_dictionary())
+            .unwrap_or_default()
+    }
+
+    pub fn clear_last_error(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.last_error = None;
+        }
+    }
+
+    pub fn take_pending(&self) -> Vec<Diagnostic> {
+        self.state
+            .lock()
+            .map(|mut state| std::mem::take(&mut state.pending))
+            .unwrap_or_default()
+    }
+}
+
+thread_local! {
+    static CURRENT_ENDPOINT: RefCell<Option<Diagnostics>> = const { RefCell::new(None) };
+}
+
+// Diagnostics reported outside of an endpoint, such as while loading settings. They are logged right away.
+static GLOBAL: LazyLock<Diagnostics> = LazyLock::new(Diagnostics::default);
+
+// Attributes everything reported while running `f` to the given endpoint, so backends don't need to know which endpoint
+// they belong to.
+pub fn with_endpoint<R>(diagnostics: &Diagnostics, f: impl FnOnce() -> R) -> R {
+    let previous = CURRENT_ENDPOINT.with(|current| current.replace(Some(diagnostics.clone())));
+    let result = f();
+    CURRENT_ENDPOINT.with(|current| current.replace(previous));
+    result
+}
+
+pub fn report(code: ErrorCode, severity: Severity, message: impl Into<String>) {
+    let endpoint = CURRENT_ENDPOINT.with(|current| current.borrow().clone());
+
+    match endpoint {
+        Some(diagnostics) => diagnostics.report(code, severity, message),
+        None => {
+            GLOBAL.report(code, severity, message);
+
+            for diagnostic in GLOBAL.take_pending() {
+                log(&diagnostic, None);
+            }
+        }
+    }
+}
+
+pub fn error(code: ErrorCode, message: impl Into<String>) {
+    report(code, Severity::Error, message);
+}
+
+pub fn warning(code: ErrorCode, message: impl Into<String>) {
+    report(code, Severity::Warning, message);
+}
+
+pub fn verbose(code: ErrorCode, message: impl Into<String>) {
+    report(code, Severity::Verbose, message);
+}
+
+// Logs to the sink if there is one, or to the Godot output otherwise. Sinks receive the diagnostic as a dictionary.
+pub fn log(diagnostic: &Diagnostic, sink: Option<&Callable>) {
+    if !diagnostic.is_logged() {
+        return;
+    }
+
+    if let Some(sink) = sink.filter(|sink| sink.is_valid()) {
+        sink.call(&[diagnostic.to_dictionary().to_variant()]);
+        return;
+    }
+
+    let Diagnostic {
+        code,
+        message,
+        repeated,
+        ..
+    } = diagnostic;
+
+    let repeated = match repeated {
+        0 => String::new(),
+        repeated => format!(" (repeated {repeated} times)"),
+    };
+
+    match diagnostic.severity {
+        Severity::Error => godot_error!("{message} [{code:?}]{repeated}"),
+        Severity::Warning => godot_warn!("{message} [{code:?}]{repeated}"),
+        Severity::Verbose => godot_print!("{message} [{code:?}]{repeated}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report_warning(diagnostics: &Diagnostics, now: Instant, message: &str) {
+        diagnostics.report_at(now, ErrorCode::InvalidFrame, Severity::Warning, message.to_owned());
+    }
+
+    #[test]
+    fn repeats_are_suppressed_and_counted() {
+        let diagnostics = Diagnostics::default();
+        let start = Instant::now();
+
+        report_warning(&diagnostics, start, "Invalid frame.");
+        report_warning(&diagnostics, start + Duration::from_secs(1), "Invalid frame.");
+        report_warning(&diagnostics, start + Duration::from_secs(2), "Invalid frame.");
+
+        let pending = diagnostics.take_pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].repeated, 0);
+
+        report_warning(&diagnostics, start + RATE_LIMIT, "Invalid frame.");
+
+        let pending = diagnostics.take_pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].repeated, 2);
+    }
+
+    #[test]
+    fn different_messages_are_rate_limited_separately() {
+        let diagnostics = Diagnostics::default();
+        let start = Instant::now();
+
+        report_warning(&diagnostics, start, "First.");
+        report_warning(&diagnostics, start, "Second.");
+        diagnostics.report_at(start, ErrorCode::InvalidTexture, Severity::Warning, "First.".to_owned());
+
+        assert_eq!(diagnostics.take_pending().len(), 3);
+    }
+
+    #[test]
+    fn expired_records_are_pruned() {
+        let diagnostics = Diagnostics::default();
+        let start = Instant::now();
+
+        for index in 0..100 {
+            report_warning(&diagnostics, start, &format!("Frame {index} is invalid."));
+        }
+
+        report_warning(&diagnostics, start + RATE_LIMIT, "Frame 100 is invalid.");
+
+        assert_eq!(diagnostics.state.lock().unwrap().records.len(), 1);
+    }
+
+    #[test]
+    fn warnings_and_errors_become_the_last_error() {
+        let diagnostics = Diagnostics::default();
+        let start = Instant::now();
+
+        diagnostics.report_at(
+            start,
+            ErrorCode::BackendSelected,
+            Severity::Verbose,
+            "Selected.".to_owned(),
+        );
+        assert!(diagnostics.last_error().is_none());
+
+        report_warning(&diagnostics, start, "Invalid frame.");
+        report_warning(&diagnostics, start, "Invalid frame.");
+        assert_eq!(diagnostics.last_error().unwrap().message, "Invalid frame.");
+
+        diagnostics.clear_last_error();
+        assert!(diagnostics.last_error().is_none());
+    }
+}
@@ -0,0 +1,5 @@
+mod plugin;
+mod sender_dock;
+
+pub use plugin::*;
+pub use sender_dock::*;
@@ -0,0 +1,29 @@
+use godot::classes::{EditorPlugin, IEditorPlugin};
+use godot::prelude::*;
+
+use crate::editor::SpoutSenderDock;
+
+#[derive(GodotClass)]
+#[class(tool, init, base=EditorPlugin)]
+pub struct SpoutEditorPlugin {
+    dock: Option<Gd<SpoutSenderDock>>,
+    base: Base<EditorPlugin>,
+}
+
+#[godot_api]
+impl IEditorPlugin for SpoutEditorPlugin {
+    fn enter_tree(&mut self) {
+        let dock = SpoutSenderDock::new_alloc();
+        self.base_mut().add_control_to_bottom_panel(&dock, "Spout");
+        self.dock = Some(dock);
+    }
+
+    fn exit_tree(&mut self) {
+        let Some(mut dock) = self.dock.take() else {
+            return;
+        };
+
+        self.base_mut().remove_control_from_bottom_panel(&dock);
+        dock.queue_free();
+    }
+}
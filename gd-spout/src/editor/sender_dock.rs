@@ -0,0 +1,200 @@
+use godot::classes::item_list::IconMode;
+use godot::classes::{IItemList, ItemList, Label, Texture2D};
+use godot::obj::EngineEnum;
+use godot::prelude::*;
+use std::path::Path;
+
+use crate::manager::SpoutManager;
+use crate::resource::{SpoutReceiverTexture, SpoutUpdateMode};
+use crate::settings;
+use crate::spout::discovery;
+
+// Thumbnails are only received when the dock refreshes, so watching senders costs next to nothing.
+const REFRESH_INTERVAL: f64 = 1.0;
+const THUMBNAIL_SIZE: Vector2i = Vector2i::new(160, 90);
+
+struct SenderEntry {
+    name: String,
+    host: Option<String>,
+    receiver: Gd<SpoutReceiverTexture>,
+    last_frame: Option<i64>,
+    frames_per_second: Option<f64>,
+}
+
+impl SenderEntry {
+    fn new(name: &str) -> Self {
+        let mut receiver = SpoutReceiverTexture::new_gd();
+
+        {
+            let mut receiver = receiver.bind_mut();
+            receiver.set_update_mode(SpoutUpdateMode::Manual);
+            receiver.set_sender_name(name.into());
+        }
+
+        let host = discovery::sender_host_path(name).map(|path| {
+            Path::new(&path)
+                .file_name()
+                .map_or(path.clone(), |file_name| file_name.to_string_lossy().into_owned())
+        });
+
+        Self {
+            name: name.to_owned(),
+            host,
+            receiver,
+            last_frame: None,
+            frames_per_second: None,
+        }
+    }
+
+    fn refresh(&mut self, elapsed: f64, receiving_enabled: bool) {
+        let frame = {
+            let receiver = self.receiver.bind();
+            let status = receiver.status();
+            status.connected.then_some(status.frame)
+        };
+
+        // Frames counted by the sender give its rate even though only a few of them are received.
+        self.frames_per_second = match (self.last_frame, frame) {
+            (Some(last_frame), Some(frame)) if frame >= last_frame => Some((frame - last_frame) as f64 / elapsed),
+            _ => None,
+        };
+        self.last_frame = frame;
+
+        if receiving_enabled {
+            self.receiver.bind().poll();
+        }
+    }
+
+    fn description(&self) -> String {
+        let receiver = self.receiver.bind();
+        let status = receiver.status();
+
+        let details = if status.connected {
+            let frames_per_second = self
+                .frames_per_second
+                .map_or("-".to_owned(), |frames_per_second| format!("{frames_per_second:.0}"));
+
+            format!(
+                "{}x{} {} {frames_per_second} fps",
+                status.width,
+                status.height,
+                status.format.as_str()
+            )
+        } else {
+            "Waiting for frames".to_owned()
+        };
+
+        let host = self.host.as_deref().unwrap_or("-");
+        format!("{}\n{details}\n{host}", self.name)
+    }
+}
+
+// Lists the senders the manager discovered. Dragging a sender onto a texture property assigns a receiver for it.
+#[derive(GodotClass)]
+#[class(tool, init, base=ItemList)]
+pub struct SpoutSenderDock {
+    senders: Vec<SenderEntry>,
+    since_refresh: f64,
+    base: Base<ItemList>,
+}
+
+#[godot_api]
+impl IItemList for SpoutSenderDock {
+    fn ready(&mut self) {
+        let mut base = self.base_mut();
+        base.set_name("Spout");
+        base.set_custom_minimum_size(Vector2::new(0.0, 200.0));
+        base.set_icon_mode(IconMode::TOP);
+        base.set_fixed_icon_size(THUMBNAIL_SIZE);
+        base.set_max_columns(0);
+        base.set_same_column_width(true);
+    }
+
+    fn exit_tree(&mut self) {
+        self.senders.clear();
+        self.base_mut().clear();
+    }
+
+    fn process(&mut self, delta: f64) {
+        self.since_refresh += delta;
+
+        if self.since_refresh < REFRESH_INTERVAL || !self.base().is_visible_in_tree() {
+            return;
+        }
+
+        let elapsed = std::mem::take(&mut self.since_refresh);
+        self.refresh(elapsed);
+    }
+
+    fn get_drag_data(&mut self, at_position: Vector2) -> Variant {
+        let index = self.base().get_item_at_position_ex(at_position).exact(true).done();
+
+        let Some(name) = usize::try_from(index)
+            .ok()
+            .and_then(|index| self.senders.get(index))
+            .map(|entry| entry.name.clone())
+        else {
+            return Variant::nil();
+        };
+
+        let mut receiver = SpoutReceiverTexture::new_gd();
+        receiver.bind_mut().set_sender_name(name.as_str().into());
+
+        let mut preview = Label::new_alloc();
+        preview.set_text(&name);
+        self.base_mut().set_drag_preview(&preview);
+
+        // The inspector accepts resources dragged in this shape, like resources dragged from the FileSystem dock.
+        dict! {
+            "type": "resource",
+            "resource": receiver,
+            "from": self.to_gd(),
+        }
+        .to_variant()
+    }
+}
+
+impl SpoutSenderDock {
+    fn refresh(&mut self, elapsed: f64) {
+        let Some(manager) = SpoutManager::singleton() else {
+            return;
+        };
+
+        let names = manager.bind().discovered_senders().to_vec();
+        let receiving_enabled = settings::is_receiving_enabled();
+
+        self.senders.retain(|entry| names.contains(&entry.name));
+
+        for (index, name) in names.iter().enumerate() {
+            if self.senders.get(index).is_none_or(|entry| &entry.name != name) {
+                let entry = self
+                    .senders
+                    .iter()
+                    .position(|entry| &entry.name == name)
+                    .map_or_else(|| SenderEntry::new(name), |position| self.senders.remove(position));
+
+                self.senders.insert(index, entry);
+            }
+        }
+
+        for entry in &mut self.senders {
+            entry.refresh(elapsed, receiving_enabled);
+        }
+
+        let items: Vec<(String, Gd<Texture2D>)> = self
+            .senders
+            .iter()
+            .map(|entry| (entry.description(), entry.receiver.clone().upcast()))
+            .collect();
+
+        let mut base = self.base_mut();
+        base.set_item_count(items.len() as i32);
+
+        for (index, (description, thumbnail)) in items.iter().enumerate() {
+            let index = index as i32;
+            base.set_item_text(index, description);
+            base.set_item_icon(index, thumbnail);
+            base.set_item_tooltip(index, "Drag onto a texture property to receive this sender.");
+        }
+    }
+}
@@ -1,12 +1,36 @@
+mod diagnostics;
+mod editor;
+mod manager;
+mod monitors;
 mod node;
+mod overrides;
 mod resource;
+mod settings;
 mod spout;
 
+pub use editor::*;
 use godot::prelude::*;
+pub use manager::*;
 pub use node::*;
 pub use resource::*;
 
 pub struct GdSpoutExtension;
 
 #[gdextension]
-unsafe impl ExtensionLibrary for GdSpoutExtension {}
+unsafe impl ExtensionLibrary for GdSpoutExtension {
+    fn on_level_init(level: InitLevel) {
+        if level == InitLevel::Scene {
+            settings::register();
+            overrides::load();
+            SpoutManager::register_singleton();
+            monitors::register();
+        }
+    }
+
+    fn on_level_deinit(level: InitLevel) {
+        if level == InitLevel::Scene {
+            monitors::unregister();
+            SpoutManager::unregister_singleton();
+        }
+    }
+}
@@ -0,0 +1,525 @@
+use godot::classes::{Engine, Node, Object, RenderingServer, Texture2D, Time};
+use godot::prelude::*;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::diagnostics;
+use crate::diagnostics::Diagnostics;
+use crate::monitors;
+use crate::overrides;
+use crate::resource::{SpoutConnectMode, SpoutReceiverTexture};
+use crate::settings;
+use crate::spout::discovery;
+use crate::spout::receiver::ReceiverHandle;
+use crate::spout::sender::{SenderHandle, SenderTexture};
+
+const DISCOVERY_INTERVAL_MSEC: u64 = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct EndpointId(u64);
+
+pub(crate) enum SenderSource {
+    Texture(Option<Gd<Texture2D>>),
+    RdTexture(Arc<Mutex<Rid>>),
+}
+
+impl SenderSource {
+    // Returns whether a frame was queued.
+    fn send(&self, handle: &SenderHandle) -> bool {
+        match self {
+            SenderSource::Texture(texture) => {
+                let Some(texture) = texture else {
+                    return false;
+                };
+
+                handle.send_resource(SenderTexture::Texture(texture.get_rid()));
+            }
+            SenderSource::RdTexture(rid) => {
+                if !rid.lock().is_ok_and(|rid| rid.is_valid()) {
+                    return false;
+                }
+
+                handle.send_shared_rd_texture(rid.clone());
+            }
+        }
+
+        true
+    }
+}
+
+struct SenderEntry {
+    owner: InstanceId,
+    name: String,
+    source: SenderSource,
+    log_sink: Option<Callable>,
+    handle: SenderHandle,
+}
+
+impl SenderEntry {
+    fn can_process(&self) -> bool {
+        Gd::<Node>::try_from_instance_id(self.owner)
+            .map(|node| node.can_process())
+            .unwrap_or(true)
+    }
+}
+
+struct ReceiverEntry {
+    owner: InstanceId,
+    connect_mode: SpoutConnectMode,
+    sender_name: String,
+    resolved_sender_name: Option<String>,
+    // Decided by the receiver after every poll, based on its update mode.
+    polling: bool,
+    log_sink: Option<Callable>,
+    handle: ReceiverHandle,
+}
+
+impl ReceiverEntry {
+    fn update_resource(&self) {
+        // Receivers without a sender have nothing to connect to yet.
+        if self.resolved_sender_name.is_some() {
+            self.handle.update_resource();
+        }
+    }
+
+    fn resolve_sender_name(&mut self, active_sender: Option<&str>, discovered: &[String], recent: &[String]) {
+        let resolved = match self.connect_mode {
+            SpoutConnectMode::ByName if !self.sender_name.is_empty() => Some(self.sender_name.as_str()),
+            SpoutConnectMode::ByName | SpoutConnectMode::ActiveSender => active_sender,
+            SpoutConnectMode::FirstAvailable => discovered.first().map(String::as_str),
+            SpoutConnectMode::MostRecent => recent.last().map(String::as_str),
+        };
+
+        // When nothing can be resolved the previous sender is kept, so its last frame stays visible.
+        let Some(resolved) = resolved else {
+            return;
+        };
+
+        if self.resolved_sender_name.as_deref() == Some(resolved) {
+            return;
+        }
+
+        self.handle.set_sender_name(resolved);
+        self.resolved_sender_name = Some(resolved.to_owned());
+    }
+}
+
+#[derive(Default)]
+struct SpoutStats {
+    frames_sent: u64,
+    frames_received: u64,
+}
+
+#[derive(GodotClass)]
+#[class(tool, init, base=Object)]
+pub struct SpoutManager {
+    #[var]
+    #[init(val = true)]
+    enabled: bool,
+    next_endpoint_id: u64,
+    senders: HashMap<EndpointId, SenderEntry>,
+    receivers: HashMap<EndpointId, ReceiverEntry>,
+    stats: SpoutStats,
+    discovered_senders: Vec<String>,
+    recent_senders: Vec<String>,
+    last_discovery_msec: Option<u64>,
+    pre_draw_callback: Option<Callable>,
+    post_draw_callback: Option<Callable>,
+    base: Base<Object>,
+}
+
+#[godot_api]
+impl SpoutManager {
+    #[func]
+    fn get_senders(&self) -> Array<Dictionary> {
+        self.senders
+            .values()
+            .map(|entry| endpoint_info(entry.owner, &entry.name))
+            .collect()
+    }
+
+    #[func]
+    fn get_receivers(&self) -> Array<Dictionary> {
+        self.receivers
+            .values()
+            .map(|entry| {
+                let name = entry.resolved_sender_name.as_deref().unwrap_or(&entry.sender_name);
+                endpoint_info(entry.owner, name)
+            })
+            .collect()
+    }
+
+    #[func]
+    fn get_discovered_senders(&self) -> PackedStringArray {
+        self.discovered_senders
+            .iter()
+            .map(|name| GString::from(name.as_str()))
+            .collect()
+    }
+
+    #[func]
+    fn get_stats(&self) -> Dictionary {
+        dict! {
+            "senders": self.senders.len() as i64,
+            "receivers": self.receivers.len() as i64,
+            "frames_sent": self.stats.frames_sent as i64,
+            "frames_received": self.stats.frames_received as i64,
+        }
+    }
+
+    #[func]
+    fn on_pre_draw(&mut self) {
+        self.refresh_discovered_senders();
+        self.flush_diagnostics();
+
+        monitors::next_frame();
+        monitors::set_endpoint_counts(self.senders.len(), self.receivers.len());
+
+        if !self.enabled {
+            return;
+        }
+
+        let receiving_enabled = settings::is_receiving_enabled();
+        let mut polled = Vec::new();
+
+        for (id, entry) in &self.receivers {
+            // Idle receivers are still notified, so they can show their fallback and pick their next update.
+            if entry.polling && receiving_enabled {
+                entry.update_resource();
+            }
+
+            polled.push((*id, entry.owner, entry.handle.take_updated()));
+        }
+
+        self.stats.frames_received += polled.iter().filter(|(_, _, updated)| *updated).count() as u64;
+
+        let mut polling = Vec::new();
+
+        {
+            // Receivers are notified through the base guard, so signal handlers are free to call back into the manager.
+            let _guard = self.base_mut();
+
+            for (id, owner, updated) in polled {
+                if let Ok(mut texture) = Gd::<SpoutReceiverTexture>::try_from_instance_id(owner) {
+                    polling.push((id, texture.bind_mut().on_receiver_polled(updated)));
+                }
+            }
+        }
+
+        for (id, polling) in polling {
+            if let Some(entry) = self.receivers.get_mut(&id) {
+                entry.polling = polling;
+            }
+        }
+    }
+
+    #[func]
+    fn on_post_draw(&mut self) {
+        if !self.enabled {
+            return;
+        }
+
+        for entry in self.senders.values() {
+            if !entry.can_process() {
+                continue;
+            }
+
+            if entry.source.send(&entry.handle) {
+                self.stats.frames_sent += 1;
+            }
+        }
+    }
+}
+
+impl SpoutManager {
+    const SINGLETON_NAME: &'static str = "SpoutManager";
+
+    pub(crate) fn register_singleton() {
+        let manager = SpoutManager::new_alloc();
+        Engine::singleton().register_singleton(Self::SINGLETON_NAME, &manager);
+    }
+
+    pub(crate) fn unregister_singleton() {
+        let Some(mut manager) = Self::singleton() else {
+            return;
+        };
+
+        Engine::singleton().unregister_singleton(Self::SINGLETON_NAME);
+        manager.bind_mut().shutdown();
+        manager.free();
+    }
+
+    // Returns `None` once the extension has been deinitialized, so endpoints dropped late can skip unregistering.
+    pub(crate) fn singleton() -> Option<Gd<Self>> {
+        let engine = Engine::singleton();
+
+        if !engine.has_singleton(Self::SINGLETON_NAME) {
+            return None;
+        }
+
+        engine
+            .get_singleton(Self::SINGLETON_NAME)
+            .and_then(|singleton| singleton.try_cast::<Self>().ok())
+    }
+
+    pub(crate) fn register_sender(&mut self, owner: InstanceId, name: &str, source: SenderSource) -> EndpointId {
+        let id = self.next_endpoint_id();
+        let name = overrides::sender_name(name);
+
+        self.senders.insert(
+            id,
+            SenderEntry {
+                owner,
+                handle: SenderHandle::new(&name),
+                name,
+                source,
+                log_sink: None,
+            },
+        );
+
+        self.connect_frame_callbacks();
+        id
+    }
+
+    pub(crate) fn set_sender_name(&mut self, id: EndpointId, name: &str) {
+        if let Some(entry) = self.senders.get_mut(&id) {
+            entry.name = overrides::sender_name(name);
+            entry.handle.set_sender_name(&entry.name);
+        }
+    }
+
+    pub(crate) fn set_sender_async_readback(&mut self, id: EndpointId, async_readback: bool) {
+        if let Some(entry) = self.senders.get_mut(&id) {
+            entry.handle.set_async_readback(async_readback);
+        }
+    }
+
+    pub(crate) fn set_sender_source(&mut self, id: EndpointId, source: SenderSource) {
+        if let Some(entry) = self.senders.get_mut(&id) {
+            entry.source = source;
+        }
+    }
+
+    pub(crate) fn unregister_sender(&mut self, id: EndpointId) {
+        if let Some(entry) = self.senders.remove(&id) {
+            entry.handle.release();
+        }
+    }
+
+    pub(crate) fn endpoint_diagnostics(&self, id: EndpointId) -> Option<Diagnostics> {
+        let sender = self.senders.get(&id).map(|entry| entry.handle.diagnostics());
+        let receiver = || self.receivers.get(&id).map(|entry| entry.handle.diagnostics());
+
+        sender.or_else(receiver).cloned()
+    }
+
+    // Endpoints hold on to their log sink and hand it to the manager whenever they are registered or it changes.
+    pub(crate) fn update_log_sink(endpoint: Option<EndpointId>, log_sink: &Callable) {
+        if let (Some(id), Some(mut manager)) = (endpoint, Self::singleton()) {
+            manager.bind_mut().set_endpoint_log_sink(id, log_sink.clone());
+        }
+    }
+
+    pub(crate) fn set_endpoint_log_sink(&mut self, id: EndpointId, log_sink: Callable) {
+        let log_sink = Some(log_sink).filter(Callable::is_valid);
+
+        if let Some(entry) = self.senders.get_mut(&id) {
+            entry.log_sink = log_sink;
+        } else if let Some(entry) = self.receivers.get_mut(&id) {
+            entry.log_sink = log_sink;
+        }
+    }
+
+    pub(crate) fn register_receiver(&mut self, owner: InstanceId) -> (EndpointId, ReceiverHandle) {
+        let id = self.next_endpoint_id();
+        let handle = ReceiverHandle::new();
+
+        self.receivers.insert(
+            id,
+            ReceiverEntry {
+                owner,
+                connect_mode: SpoutConnectMode::default(),
+                sender_name: String::new(),
+                resolved_sender_name: None,
+                polling: true,
+                log_sink: None,
+                handle: handle.clone(),
+            },
+        );
+
+        self.connect_frame_callbacks();
+        self.resolve_receivers();
+        (id, handle)
+    }
+
+    pub(crate) fn set_receiver_sender_name(&mut self, id: EndpointId, sender_name: &str) {
+        if let Some(entry) = self.receivers.get_mut(&id) {
+            entry.sender_name = overrides::receiver_sender_name(sender_name);
+            self.resolve_receivers();
+        }
+    }
+
+    pub(crate) fn set_receiver_connect_mode(&mut self, id: EndpointId, connect_mode: SpoutConnectMode) {
+        if let Some(entry) = self.receivers.get_mut(&id) {
+            entry.connect_mode = connect_mode;
+            self.resolve_receivers();
+        }
+    }
+
+    // Updates the receiver once, regardless of whether it is polling.
+    pub(crate) fn poll_receiver(&self, id: EndpointId) {
+        if let Some(entry) = self.receivers.get(&id) {
+            entry.update_resource();
+        }
+    }
+
+    pub(crate) fn unregister_receiver(&mut self, id: EndpointId) {
+        if let Some(entry) = self.receivers.remove(&id) {
+            entry.handle.release();
+        }
+    }
+
+    pub(crate) fn discovered_senders(&self) -> &[String] {
+        &self.discovered_senders
+    }
+
+    pub(crate) fn is_sender_discovered(&self, name: &str) -> bool {
+        self.discovered_senders.iter().any(|sender| sender == name)
+    }
+
+    fn refresh_discovered_senders(&mut self) {
+        let now = Time::singleton().get_ticks_msec();
+
+        if self
+            .last_discovery_msec
+            .is_some_and(|last| now.saturating_sub(last) < DISCOVERY_INTERVAL_MSEC)
+        {
+            return;
+        }
+
+        self.last_discovery_msec = Some(now);
+
+        let mut senders = discovery::sender_names();
+        senders.sort();
+
+        if senders != self.discovered_senders {
+            self.recent_senders.retain(|name| senders.contains(name));
+
+            for name in &senders {
+                if !self.recent_senders.contains(name) {
+                    self.recent_senders.push(name.clone());
+                }
+            }
+
+            self.discovered_senders = senders;
+            self.notify_sender_list_changed();
+        }
+
+        // The active sender can change without the list of senders changing.
+        self.resolve_receivers();
+    }
+
+    // Diagnostics reported since the last frame are logged here, where the log sinks can be called.
+    fn flush_diagnostics(&mut self) {
+        let senders = self
+            .senders
+            .values()
+            .map(|entry| (entry.handle.diagnostics().take_pending(), entry.log_sink.clone()));
+
+        let receivers = self
+            .receivers
+            .values()
+            .map(|entry| (entry.handle.diagnostics().take_pending(), entry.log_sink.clone()));
+
+        let reports: Vec<_> = senders
+            .chain(receivers)
+            .filter(|(pending, _)| !pending.is_empty())
+            .collect();
+
+        // Sinks are called through the base guard, so they are free to call back into the manager.
+        let _guard = self.base_mut();
+
+        for (pending, log_sink) in reports {
+            for diagnostic in pending {
+                diagnostics::log(&diagnostic, log_sink.as_ref());
+            }
+        }
+    }
+
+    fn resolve_receivers(&mut self) {
+        let active_sender = discovery::active_sender();
+
+        for entry in self.receivers.values_mut() {
+            entry.resolve_sender_name(active_sender.as_deref(), &self.discovered_senders, &self.recent_senders);
+        }
+    }
+
+    fn notify_sender_list_changed(&mut self) {
+        if !Engine::singleton().is_editor_hint() {
+            return;
+        }
+
+        // Lets the inspector pick up the new sender suggestions.
+        let owners: Vec<InstanceId> = self.receivers.values().map(|entry| entry.owner).collect();
+        let _guard = self.base_mut();
+
+        for owner in owners {
+            if let Ok(mut object) = Gd::<Object>::try_from_instance_id(owner) {
+                object.notify_property_list_changed();
+            }
+        }
+    }
+
+    fn next_endpoint_id(&mut self) -> EndpointId {
+        self.next_endpoint_id += 1;
+        EndpointId(self.next_endpoint_id)
+    }
+
+    fn connect_frame_callbacks(&mut self) {
+        let mut rendering_server = RenderingServer::singleton();
+
+        if self.pre_draw_callback.is_none() {
+            let callable = self.base().callable("on_pre_draw");
+            rendering_server.connect("frame_pre_draw", &callable);
+            self.pre_draw_callback = Some(callable);
+        }
+
+        if self.post_draw_callback.is_none() {
+            let callable = self.base().callable("on_post_draw");
+            rendering_server.connect("frame_post_draw", &callable);
+            self.post_draw_callback = Some(callable);
+        }
+    }
+
+    // Backends are released before the manager goes away, while the rendering server is still around.
+    fn shutdown(&mut self) {
+        let mut rendering_server = RenderingServer::singleton();
+
+        if let Some(callback) = self.pre_draw_callback.take() {
+            rendering_server.disconnect("frame_pre_draw", &callback);
+        }
+
+        if let Some(callback) = self.post_draw_callback.take() {
+            rendering_server.disconnect("frame_post_draw", &callback);
+        }
+
+        for (_, entry) in self.senders.drain() {
+            entry.handle.release();
+        }
+
+        for (_, entry) in self.receivers.drain() {
+            entry.handle.release();
+        }
+    }
+}
+
+fn endpoint_info(owner: InstanceId, name: &str) -> Dictionary {
+    let owner = Gd::<Object>::try_from_instance_id(owner)
+        .map(|owner| owner.to_variant())
+        .unwrap_or_default();
+
+    dict! {
+        "name": name,
+        "owner": owner,
+    }
+}
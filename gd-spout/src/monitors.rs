@@ -0,0 +1,256 @@
+use godot::classes::Performance;
+use godot::prelude::*;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+// Instrumentation is accumulated from any thread during a frame and published once per frame by `next_frame`, rates
+// are averaged over a window of about a second.
+static SEND_NSEC: AtomicU64 = AtomicU64::new(0);
+static RECEIVE_NSEC: AtomicU64 = AtomicU64::new(0);
+static FRAMES_SENT: AtomicU64 = AtomicU64::new(0);
+static FRAMES_RECEIVED: AtomicU64 = AtomicU64::new(0);
+static BYTES_COPIED: AtomicU64 = AtomicU64::new(0);
+static DROPPED_FRAMES: AtomicU64 = AtomicU64::new(0);
+static SENDERS: AtomicU64 = AtomicU64::new(0);
+static RECEIVERS: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Clone, Copy, Default)]
+struct Published {
+    send_msec: f64,
+    receive_msec: f64,
+    frames_sent_per_second: f64,
+    frames_received_per_second: f64,
+    bytes_copied_per_second: f64,
+}
+
+struct Window {
+    started: Instant,
+    frames_sent: u64,
+    frames_received: u64,
+    bytes_copied: u64,
+}
+
+impl Window {
+    fn new(started: Instant) -> Self {
+        Self {
+            started,
+            frames_sent: 0,
+            frames_received: 0,
+            bytes_copied: 0,
+        }
+    }
+
+    // Adds the counts of one frame. Once the window spans `RATE_WINDOW`, the rates of frames sent, frames received and
+    // bytes copied are returned and a new window starts.
+    fn add(&mut self, now: Instant, frames_sent: u64, frames_received: u64, bytes_copied: u64) -> Option<[f64; 3]> {
+        self.frames_sent += frames_sent;
+        self.frames_received += frames_received;
+        self.bytes_copied += bytes_copied;
+
+        let elapsed = now.duration_since(self.started);
+
+        if elapsed < RATE_WINDOW {
+            return None;
+        }
+
+        let seconds = elapsed.as_secs_f64();
+        let rates = [
+            self.frames_sent as f64 / seconds,
+            self.frames_received as f64 / seconds,
+            self.bytes_copied as f64 / seconds,
+        ];
+
+        *self = Window::new(now);
+        Some(rates)
+    }
+}
+
+struct MonitorState {
+    window: Window,
+    published: Published,
+}
+
+static STATE: Mutex<Option<MonitorState>> = Mutex::new(None);
+
+#[derive(Clone, Copy)]
+enum Monitor {
+    SendTime,
+    ReceiveTime,
+    FramesSent,
+    FramesReceived,
+    DroppedFrames,
+    BytesCopied,
+    Senders,
+    Receivers,
+}
+
+const MONITORS: [Monitor; 8] = [
+    Monitor::SendTime,
+    Monitor::ReceiveTime,
+    Monitor::FramesSent,
+    Monitor::FramesReceived,
+    Monitor::DroppedFrames,
+    Monitor::BytesCopied,
+    Monitor::Senders,
+    Monitor::Receivers,
+];
+
+impl Monitor {
+    fn id(self) -> &'static str {
+        match self {
+            Monitor::SendTime => "gd_spout/send_time_ms",
+            Monitor::ReceiveTime => "gd_spout/receive_time_ms",
+            Monitor::FramesSent => "gd_spout/frames_sent_per_second",
+            Monitor::FramesReceived => "gd_spout/frames_received_per_second",
+            Monitor::DroppedFrames => "gd_spout/dropped_frames",
+            Monitor::BytesCopied => "gd_spout/bytes_copied_per_second",
+            Monitor::Senders => "gd_spout/senders",
+            Monitor::Receivers => "gd_spout/receivers",
+        }
+    }
+
+    fn value(self) -> Variant {
+        let published = published();
+
+        match self {
+            Monitor::SendTime => published.send_msec.to_variant(),
+            Monitor::ReceiveTime => published.receive_msec.to_variant(),
+            Monitor::FramesSent => published.frames_sent_per_second.to_variant(),
+            Monitor::FramesReceived => published.frames_received_per_second.to_variant(),
+            Monitor::DroppedFrames => (DROPPED_FRAMES.load(Ordering::Relaxed) as i64).to_variant(),
+            Monitor::BytesCopied => published.bytes_copied_per_second.to_variant(),
+            Monitor::Senders => (SENDERS.load(Ordering::Relaxed) as i64).to_variant(),
+            Monitor::Receivers => (RECEIVERS.load(Ordering::Relaxed) as i64).to_variant(),
+        }
+    }
+}
+
+// The monitors show up in the Monitors tab of the debugger, under a `gd_spout` category.
+pub fn register() {
+    let mut performance = Performance::singleton();
+
+    for monitor in MONITORS {
+        if performance.has_custom_monitor(monitor.id()) {
+            continue;
+        }
+
+        let callable = Callable::from_sync_fn("gd_spout_monitor", move |_args| Ok(monitor.value()));
+        performance.add_custom_monitor(monitor.id(), &callable);
+    }
+}
+
+pub fn unregister() {
+    let mut performance = Performance::singleton();
+
+    for monitor in MONITORS {
+        if performance.has_custom_monitor(monitor.id()) {
+            performance.remove_custom_monitor(monitor.id());
+        }
+    }
+}
+
+pub fn time_send<R>(f: impl FnOnce() -> R) -> R {
+    timed(&SEND_NSEC, f)
+}
+
+pub fn time_receive<R>(f: impl FnOnce() -> R) -> R {
+    timed(&RECEIVE_NSEC, f)
+}
+
+pub fn frame_sent() {
+    FRAMES_SENT.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn frame_received() {
+    FRAMES_RECEIVED.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn frames_dropped(count: u64) {
+    DROPPED_FRAMES.fetch_add(count, Ordering::Relaxed);
+}
+
+pub fn bytes_copied(count: u64) {
+    BYTES_COPIED.fetch_add(count, Ordering::Relaxed);
+}
+
+pub fn set_endpoint_counts(senders: usize, receivers: usize) {
+    SENDERS.store(senders as u64, Ordering::Relaxed);
+    RECEIVERS.store(receivers as u64, Ordering::Relaxed);
+}
+
+pub fn next_frame() {
+    let Ok(mut state) = STATE.lock() else {
+        return;
+    };
+
+    let now = Instant::now();
+    let MonitorState { window, published } = state.get_or_insert_with(|| MonitorState {
+        window: Window::new(now),
+        published: Published::default(),
+    });
+
+    published.send_msec = SEND_NSEC.swap(0, Ordering::Relaxed) as f64 / 1_000_000.0;
+    published.receive_msec = RECEIVE_NSEC.swap(0, Ordering::Relaxed) as f64 / 1_000_000.0;
+
+    let rates = window.add(
+        now,
+        FRAMES_SENT.swap(0, Ordering::Relaxed),
+        FRAMES_RECEIVED.swap(0, Ordering::Relaxed),
+        BYTES_COPIED.swap(0, Ordering::Relaxed),
+    );
+
+    if let Some([frames_sent, frames_received, bytes_copied]) = rates {
+        published.frames_sent_per_second = frames_sent;
+        published.frames_received_per_second = frames_received;
+        published.bytes_copied_per_second = bytes_copied;
+    }
+}
+
+fn published() -> Published {
+    STATE
+        .lock()
+        .ok()
+        .and_then(|state| state.as_ref().map(|state| state.published))
+        .unwrap_or_default()
+}
+
+fn timed<R>(nsec: &AtomicU64, f: impl FnOnce() -> R) -> R {
+    let started = Instant::now();
+    let result = f();
+    nsec.fetch_add(started.elapsed().as_nanos() as u64, Ordering::Relaxed);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rates_are_published_once_the_window_is_full() {
+        let start = Instant::now();
+        let mut window = Window::new(start);
+
+        assert_eq!(window.add(start + Duration::from_millis(500), 30, 10, 1000), None);
+        assert_eq!(
+            window.add(start + Duration::from_secs(2), 30, 10, 1000),
+            Some([30.0, 10.0, 1000.0])
+        );
+    }
+
+    #[test]
+    fn a_new_window_starts_after_publishing() {
+        let start = Instant::now();
+        let mut window = Window::new(start);
+
+        window.add(start + RATE_WINDOW, 60, 60, 60);
+
+        assert_eq!(
+            window.add(start + RATE_WINDOW + Duration::from_millis(500), 5, 5, 5),
+            None
+        );
+        assert_eq!(window.add(start + RATE_WINDOW * 2, 5, 0, 0), Some([10.0, 5.0, 5.0]));
+    }
+}
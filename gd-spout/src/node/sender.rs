@@ -1,89 +1,142 @@
-use godot::classes::{Engine, Node, RenderingServer, Texture2D};
+use godot::classes::{Node, Texture2D};
 use godot::prelude::*;
 
-use crate::spout;
-use crate::spout::sender::create_sender;
-
-thread_local! {
-    static RENDERING_DRIVER_D3D12: GString = "d3d12".into();
-}
+use crate::diagnostics::{Diagnostics, ErrorCode, Severity};
+use crate::manager::{EndpointId, SenderSource, SpoutManager};
+use crate::settings;
 
 #[derive(GodotClass)]
-#[class(init, base=Node)]
+#[class(tool, init, base=Node)]
 pub struct SpoutSender {
     #[export]
     #[var(set = set_name)]
     name: GString,
     #[export]
+    #[var(set = set_texture)]
     texture: Option<Gd<Texture2D>>,
-    callback: Option<Callable>,
-    spout: Option<Box<dyn spout::sender::SpoutSender>>,
+    // Only used without a native Spout backend, where frames are read back to the CPU. Asynchronous readback avoids
+    // stalling on the frame at the cost of some latency, and is unavailable with the Compatibility renderer.
+    #[export]
+    #[var(set = set_async_readback)]
+    async_readback: bool,
+    // Receives every logged diagnostic as a dictionary instead of the Godot output.
+    #[var(set = set_log_sink)]
+    #[init(val = Callable::invalid())]
+    log_sink: Callable,
+    endpoint: Option<EndpointId>,
+    diagnostics: Option<Diagnostics>,
     base: Base<Node>,
 }
 
 impl Drop for SpoutSender {
     fn drop(&mut self) {
-        if let Some(callback) = self.callback.take() {
-            RenderingServer::singleton().disconnect("frame_post_draw", &callback);
-        }
+        self.stop();
     }
 }
 
 #[godot_api]
 impl INode for SpoutSender {
-    fn exit_tree(&mut self) {
-        if let Some(callback) = self.callback.take() {
-            RenderingServer::singleton().disconnect("frame_post_draw", &callback);
-        }
-    }
-
-    fn ready(&mut self) {
-        if Engine::singleton().is_editor_hint() {
+    fn enter_tree(&mut self) {
+        if !settings::is_sending_enabled() {
             return;
         }
 
-        if let Some(callback) = self.callback.take() {
-            RenderingServer::singleton().disconnect("frame_post_draw", &callback);
-        }
-
-        let driver_name = RenderingServer::singleton()
-            .get_current_rendering_driver_name()
-            .to_string();
-
-        let mut spout = create_sender(&driver_name);
-        spout.set_sender_name(&self.name.to_string());
-        self.spout = Some(spout);
+        self.start();
+    }
 
-        let callable = self.base().callable("on_post_draw");
-        RenderingServer::singleton().connect("frame_post_draw", &callable);
-        self.callback = Some(callable);
+    fn exit_tree(&mut self) {
+        self.stop();
     }
 }
+
 #[godot_api]
 impl SpoutSender {
     #[func]
     fn set_name(&mut self, name: GString) {
-        if let Some(spout) = &mut self.spout {
-            spout.set_sender_name(&name.to_string());
+        if let (Some(id), Some(mut manager)) = (self.endpoint, SpoutManager::singleton()) {
+            manager.bind_mut().set_sender_name(id, &name.to_string());
         }
 
         self.name = name;
     }
 
     #[func]
-    fn on_post_draw(&mut self) {
-        let Some(spout) = &mut self.spout else {
-            godot_error!("No spout sender available.");
+    fn set_texture(&mut self, texture: Option<Gd<Texture2D>>) {
+        if let (Some(id), Some(mut manager)) = (self.endpoint, SpoutManager::singleton()) {
+            manager
+                .bind_mut()
+                .set_sender_source(id, SenderSource::Texture(texture.clone()));
+        }
+
+        self.texture = texture;
+        self.warn_if_no_texture();
+    }
+
+    #[func]
+    fn set_async_readback(&mut self, async_readback: bool) {
+        if let (Some(id), Some(mut manager)) = (self.endpoint, SpoutManager::singleton()) {
+            manager.bind_mut().set_sender_async_readback(id, async_readback);
+        }
+
+        self.async_readback = async_readback;
+    }
+
+    #[func]
+    fn set_log_sink(&mut self, log_sink: Callable) {
+        SpoutManager::update_log_sink(self.endpoint, &log_sink);
+        self.log_sink = log_sink;
+    }
+
+    #[func]
+    fn get_last_error(&self) -> Dictionary {
+        self.diagnostics
+            .as_ref()
+            .map_or_else(Dictionary::new, Diagnostics::last_error_dictionary)
+    }
+
+    #[func]
+    fn clear_last_error(&self) {
+        self.diagnostics.iter().for_each(Diagnostics::clear_last_error);
+    }
+}
+
+impl SpoutSender {
+    fn start(&mut self) {
+        self.stop();
+
+        let Some(mut manager) = SpoutManager::singleton() else {
             return;
         };
 
-        let Some(texture) = &self.texture else {
-            godot_error!("No texture available.");
+        let owner = self.base().instance_id();
+        let source = SenderSource::Texture(self.texture.clone());
+        let mut manager = manager.bind_mut();
+        let id = manager.register_sender(owner, &self.name.to_string(), source);
+        manager.set_sender_async_readback(id, self.async_readback);
+        manager.set_endpoint_log_sink(id, self.log_sink.clone());
+
+        self.endpoint = Some(id);
+        self.diagnostics = manager.endpoint_diagnostics(id);
+        self.warn_if_no_texture();
+    }
+
+    // Reported once when the texture goes missing, the sender itself just idles without one.
+    fn warn_if_no_texture(&self) {
+        if let (None, Some(diagnostics)) = (&self.texture, &self.diagnostics) {
+            diagnostics.report(ErrorCode::NoTexture, Severity::Warning, "No texture to send.");
+        }
+    }
+
+    fn stop(&mut self) {
+        let Some(id) = self.endpoint.take() else {
             return;
         };
 
-        let source_rid = RenderingServer::singleton().texture_get_rd_texture(texture.get_rid());
+        self.diagnostics = None;
 
-        spout.send_resource(source_rid);
+        // Unregistering releases the Spout sender, so it is registered again when the node re-enters a tree.
+        if let Some(mut manager) = SpoutManager::singleton() {
+            manager.bind_mut().unregister_sender(id);
+        }
     }
 }
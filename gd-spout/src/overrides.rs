@@ -0,0 +1,205 @@
+use godot::classes::Os;
+use godot::prelude::*;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::diagnostics;
+use crate::diagnostics::ErrorCode;
+use crate::settings;
+
+const SENDER_PREFIX_ARG: &str = "--spout-sender-prefix";
+const MAP_ARG: &str = "--spout-map";
+const SENDER_PREFIX_ENV: &str = "GD_SPOUT_SENDER_PREFIX";
+// Holds `old=new` pairs separated by semicolons.
+const MAP_ENV: &str = "GD_SPOUT_MAP";
+
+// Launch-time overrides let several instances of the same build run side by side. Mapped names apply to sender names
+// and to the sender names receivers connect to, the prefix replaces the project setting for sender names. User
+// arguments (after `--` on the command line) take precedence over environment variables.
+#[derive(Default)]
+struct Overrides {
+    sender_prefix: Option<String>,
+    name_map: HashMap<String, String>,
+}
+
+static OVERRIDES: OnceLock<Overrides> = OnceLock::new();
+
+pub fn load() {
+    let args: Vec<String> = Os::singleton()
+        .get_cmdline_user_args()
+        .as_slice()
+        .iter()
+        .map(GString::to_string)
+        .collect();
+
+    let overrides = parse(&args, |name| std::env::var(name).ok());
+    let _ = OVERRIDES.set(overrides);
+}
+
+fn parse(args: &[String], env: impl Fn(&str) -> Option<String>) -> Overrides {
+    let mut overrides = Overrides::default();
+
+    if let Some(prefix) = env(SENDER_PREFIX_ENV) {
+        overrides.sender_prefix = Some(prefix);
+    }
+
+    if let Some(map) = env(MAP_ENV) {
+        for mapping in map.split(';').filter(|mapping| !mapping.is_empty()) {
+            add_mapping(&mut overrides.name_map, mapping);
+        }
+    }
+
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_owned())),
+            None => (arg.as_str(), None),
+        };
+
+        if name != SENDER_PREFIX_ARG && name != MAP_ARG {
+            continue;
+        }
+
+        // Values are accepted both as `--arg=value` and as `--arg value`.
+        let Some(value) = inline_value.or_else(|| args.next().cloned()) else {
+            diagnostics::error(ErrorCode::InvalidArgument, format!("Missing value for: {name}"));
+            continue;
+        };
+
+        if name == SENDER_PREFIX_ARG {
+            overrides.sender_prefix = Some(value);
+        } else {
+            add_mapping(&mut overrides.name_map, &value);
+        }
+    }
+
+    overrides
+}
+
+// The name a sender is published under.
+pub fn sender_name(name: &str) -> String {
+    let overrides = OVERRIDES.get();
+    let prefix = overrides
+        .and_then(|overrides| overrides.sender_prefix.clone())
+        .unwrap_or_else(|| settings::get().sender_name_prefix);
+
+    format!("{prefix}{}", mapped_name(name))
+}
+
+// The sender a receiver connects to by name.
+pub fn receiver_sender_name(name: &str) -> String {
+    mapped_name(name).to_owned()
+}
+
+fn mapped_name(name: &str) -> &str {
+    OVERRIDES
+        .get()
+        .and_then(|overrides| overrides.name_map.get(name))
+        .map_or(name, String::as_str)
+}
+
+fn add_mapping(name_map: &mut HashMap<String, String>, mapping: &str) {
+    let Some((old, new)) = mapping.split_once('=') else {
+        diagnostics::error(
+            ErrorCode::InvalidArgument,
+            format!("Invalid Spout name mapping, expected old=new: {mapping}"),
+        );
+        return;
+    };
+
+    name_map.insert(old.to_owned(), new.to_owned());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::Diagnostics;
+
+    // Invalid input is reported to an endpoint, so nothing is logged to Godot.
+    fn parse_with(args: &[&str], env: &[(&str, &str)]) -> (Overrides, usize) {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        let diagnostics = Diagnostics::default();
+
+        let overrides = diagnostics::with_endpoint(&diagnostics, || {
+            parse(&args, |name| {
+                env.iter()
+                    .find(|(env_name, _)| *env_name == name)
+                    .map(|(_, value)| value.to_string())
+            })
+        });
+
+        (overrides, diagnostics.take_pending().len())
+    }
+
+    fn mapping(overrides: &Overrides, name: &str) -> Option<String> {
+        overrides.name_map.get(name).cloned()
+    }
+
+    #[test]
+    fn arguments_accept_inline_and_separate_values() {
+        let (overrides, errors) = parse_with(
+            &[
+                "--spout-sender-prefix=Left ",
+                "--spout-map",
+                "Main=Left Main",
+                "--spout-map=Preview=Left Preview",
+            ],
+            &[],
+        );
+
+        assert_eq!(errors, 0);
+        assert_eq!(overrides.sender_prefix.as_deref(), Some("Left "));
+        assert_eq!(mapping(&overrides, "Main").as_deref(), Some("Left Main"));
+        assert_eq!(mapping(&overrides, "Preview").as_deref(), Some("Left Preview"));
+    }
+
+    #[test]
+    fn unrelated_arguments_are_ignored() {
+        let (overrides, errors) = parse_with(&["--fullscreen", "--level=2", "Main=Other"], &[]);
+
+        assert_eq!(errors, 0);
+        assert!(overrides.sender_prefix.is_none());
+        assert!(overrides.name_map.is_empty());
+    }
+
+    #[test]
+    fn environment_map_holds_several_pairs() {
+        let (overrides, errors) = parse_with(
+            &[],
+            &[
+                (MAP_ENV, "Main=Left Main;;Preview=Left Preview;"),
+                (SENDER_PREFIX_ENV, "Left "),
+            ],
+        );
+
+        assert_eq!(errors, 0);
+        assert_eq!(overrides.sender_prefix.as_deref(), Some("Left "));
+        assert_eq!(mapping(&overrides, "Main").as_deref(), Some("Left Main"));
+        assert_eq!(mapping(&overrides, "Preview").as_deref(), Some("Left Preview"));
+    }
+
+    #[test]
+    fn arguments_take_precedence_over_environment() {
+        let (overrides, _) = parse_with(
+            &["--spout-sender-prefix", "Argument ", "--spout-map=Main=From Argument"],
+            &[
+                (SENDER_PREFIX_ENV, "Environment "),
+                (MAP_ENV, "Main=From Environment;Preview=Kept"),
+            ],
+        );
+
+        assert_eq!(overrides.sender_prefix.as_deref(), Some("Argument "));
+        assert_eq!(mapping(&overrides, "Main").as_deref(), Some("From Argument"));
+        assert_eq!(mapping(&overrides, "Preview").as_deref(), Some("Kept"));
+    }
+
+    #[test]
+    fn invalid_input_is_reported() {
+        let (overrides, errors) = parse_with(&["--spout-map=Main", "--spout-sender-prefix"], &[(MAP_ENV, "Preview")]);
+
+        assert_eq!(errors, 3);
+        assert!(overrides.sender_prefix.is_none());
+        assert!(overrides.name_map.is_empty());
+    }
+}
@@ -0,0 +1,20 @@
+use godot::classes::{CameraFeed, ICameraFeed};
+use godot::prelude::*;
+
+// Images are pushed by the owning `SpoutReceiverTexture`, which only reads frames back while the feed is active.
+#[derive(GodotClass)]
+#[class(tool, init, base=CameraFeed)]
+pub struct SpoutCameraFeed {
+    base: Base<CameraFeed>,
+}
+
+#[godot_api]
+impl ICameraFeed for SpoutCameraFeed {
+    fn activate_feed(&mut self) -> bool {
+        true
+    }
+
+    fn deactivate_feed(&mut self) {
+        // No-op
+    }
+}
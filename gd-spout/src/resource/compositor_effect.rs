@@ -0,0 +1,290 @@
+use godot::classes::compositor_effect::EffectCallbackType;
+use godot::classes::environment::ToneMapper;
+use godot::classes::notify::ObjectNotification;
+use godot::classes::rendering_device::{DataFormat, TextureSamples, TextureUsageBits};
+use godot::classes::{
+    CompositorEffect, ICompositorEffect, RdTextureView, RenderData, RenderSceneBuffersRd, RenderingDevice,
+    RenderingServer,
+};
+use godot::prelude::*;
+use std::sync::{Arc, Mutex};
+
+use crate::diagnostics;
+use crate::diagnostics::{Diagnostics, ErrorCode};
+use crate::manager::{EndpointId, SenderSource, SpoutManager};
+use crate::settings;
+use crate::spout::render_thread::call_on_render_thread;
+use crate::spout::tonemap::{TonemapSettings, Tonemapper};
+
+// Godot has no compositor stage after tonemapping, so `PostTonemap` captures after transparent objects and applies the
+// tonemapper of the effect itself.
+#[derive(GodotConvert, Var, Export, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[godot(via = i64)]
+pub enum SpoutCaptureStage {
+    PreTransparent,
+    PostTransparent,
+    #[default]
+    PostTonemap,
+}
+
+impl SpoutCaptureStage {
+    fn effect_callback_type(self) -> EffectCallbackType {
+        match self {
+            SpoutCaptureStage::PreTransparent => EffectCallbackType::PRE_TRANSPARENT,
+            SpoutCaptureStage::PostTransparent | SpoutCaptureStage::PostTonemap => EffectCallbackType::POST_TRANSPARENT,
+        }
+    }
+}
+
+// Sends the color buffer of the camera. The pre and post transparent stages send it in linear HDR, `PostTonemap` sends
+// it as seen on screen in sRGB encoded RGBA8, using the tonemap settings of the effect, which should match the
+// environment. Glow, adjustments and color correction of the environment are not applied.
+#[derive(GodotClass)]
+#[class(tool, init, base=CompositorEffect)]
+pub struct SpoutCompositorEffect {
+    #[export]
+    #[var(set = set_name)]
+    name: GString,
+    #[export]
+    #[var(set = set_stage)]
+    stage: SpoutCaptureStage,
+    #[export]
+    #[init(val = ToneMapper::LINEAR)]
+    tonemap_mode: ToneMapper,
+    #[export]
+    #[init(val = 1.0)]
+    tonemap_exposure: f32,
+    #[export]
+    #[init(val = 1.0)]
+    tonemap_white: f32,
+    #[init(val = Rid::Invalid)]
+    capture_rid: Rid,
+    capture_size: Vector2i,
+    #[init(val = DataFormat::MAX)]
+    capture_format: DataFormat,
+    tonemapper: Option<Tonemapper>,
+    #[init(val = Arc::new(Mutex::new(Rid::Invalid)))]
+    shared_capture_rid: Arc<Mutex<Rid>>,
+    // Receives every logged diagnostic as a dictionary instead of the Godot output.
+    #[var(set = set_log_sink)]
+    #[init(val = Callable::invalid())]
+    log_sink: Callable,
+    endpoint: Option<EndpointId>,
+    diagnostics: Option<Diagnostics>,
+    base: Base<CompositorEffect>,
+}
+
+impl Drop for SpoutCompositorEffect {
+    fn drop(&mut self) {
+        if let (Some(id), Some(mut manager)) = (self.endpoint.take(), SpoutManager::singleton()) {
+            manager.bind_mut().unregister_sender(id);
+        }
+
+        let capture_rid = self.capture_rid;
+        let tonemapper = self.tonemapper.take();
+
+        if capture_rid.is_valid() || tonemapper.is_some() {
+            call_on_render_thread(move || {
+                drop(tonemapper);
+
+                if let Some(mut rendering_device) = RenderingServer::singleton().get_rendering_device() {
+                    if capture_rid.is_valid() {
+                        rendering_device.free_rid(capture_rid);
+                    }
+                }
+            });
+        }
+    }
+}
+
+#[godot_api]
+impl ICompositorEffect for SpoutCompositorEffect {
+    fn on_notification(&mut self, what: ObjectNotification) {
+        if what != ObjectNotification::POSTINITIALIZE {
+            return;
+        }
+
+        let callback_type = self.stage.effect_callback_type();
+        self.base_mut().set_effect_callback_type(callback_type);
+        // With MSAA the color buffer is only complete once it is resolved.
+        self.base_mut().set_access_resolved_color(true);
+
+        if !settings::is_sending_enabled() {
+            return;
+        }
+
+        let Some(mut manager) = SpoutManager::singleton() else {
+            return;
+        };
+
+        let owner = self.base().instance_id();
+        let source = SenderSource::RdTexture(self.shared_capture_rid.clone());
+        let mut manager = manager.bind_mut();
+        let id = manager.register_sender(owner, &self.name.to_string(), source);
+        manager.set_endpoint_log_sink(id, self.log_sink.clone());
+
+        self.endpoint = Some(id);
+        self.diagnostics = manager.endpoint_diagnostics(id);
+    }
+
+    fn render_callback(&mut self, _effect_callback_type: i32, render_data: Option<Gd<RenderData>>) {
+        // Effects without an endpoint are not sending, such as in the editor.
+        let Some(effect_diagnostics) = self.diagnostics.clone() else {
+            return;
+        };
+
+        diagnostics::with_endpoint(&effect_diagnostics, || self.capture(render_data));
+    }
+}
+
+#[godot_api]
+impl SpoutCompositorEffect {
+    #[func]
+    fn set_name(&mut self, name: GString) {
+        if let (Some(id), Some(mut manager)) = (self.endpoint, SpoutManager::singleton()) {
+            manager.bind_mut().set_sender_name(id, &name.to_string());
+        }
+
+        self.name = name;
+    }
+
+    #[func]
+    fn set_stage(&mut self, stage: SpoutCaptureStage) {
+        self.stage = stage;
+        self.base_mut().set_effect_callback_type(stage.effect_callback_type());
+    }
+
+    #[func]
+    fn set_log_sink(&mut self, log_sink: Callable) {
+        SpoutManager::update_log_sink(self.endpoint, &log_sink);
+        self.log_sink = log_sink;
+    }
+
+    #[func]
+    fn get_last_error(&self) -> Dictionary {
+        self.diagnostics
+            .as_ref()
+            .map_or_else(Dictionary::new, Diagnostics::last_error_dictionary)
+    }
+
+    #[func]
+    fn clear_last_error(&self) {
+        self.diagnostics.iter().for_each(Diagnostics::clear_last_error);
+    }
+}
+
+impl SpoutCompositorEffect {
+    fn capture(&mut self, render_data: Option<Gd<RenderData>>) {
+        let Some(scene_buffers) = render_data.and_then(|render_data| render_data.get_render_scene_buffers()) else {
+            return;
+        };
+
+        let Ok(mut scene_buffers) = scene_buffers.try_cast::<RenderSceneBuffersRd>() else {
+            return;
+        };
+
+        let Some(mut rendering_device) = RenderingServer::singleton().get_rendering_device() else {
+            diagnostics::error(ErrorCode::RenderingDeviceUnavailable, "Rendering device was null.");
+            return;
+        };
+
+        let source_rid = scene_buffers.get_color_layer(0);
+        let size = scene_buffers.get_internal_size();
+
+        if !source_rid.is_valid() || size.x <= 0 || size.y <= 0 {
+            return;
+        }
+
+        // The color buffer is only complete once the frame is submitted, so it is copied here and sent by the manager.
+        self.update_capture_texture(&mut rendering_device, source_rid, size);
+
+        if !self.capture_rid.is_valid() {
+            return;
+        }
+
+        if self.stage == SpoutCaptureStage::PostTonemap {
+            self.tonemap(&mut rendering_device, source_rid, size);
+            return;
+        }
+
+        rendering_device.texture_copy(
+            source_rid,
+            self.capture_rid,
+            Vector3::ZERO,
+            Vector3::ZERO,
+            Vector3::new(size.x as f32, size.y as f32, 1.0),
+            0,
+            0,
+            0,
+            0,
+        );
+    }
+
+    fn tonemap(&mut self, rendering_device: &mut Gd<RenderingDevice>, source_rid: Rid, size: Vector2i) {
+        if self.tonemapper.is_none() {
+            self.tonemapper = Tonemapper::new(rendering_device);
+        }
+
+        let settings = TonemapSettings {
+            mode: self.tonemap_mode,
+            exposure: self.tonemap_exposure,
+            white: self.tonemap_white,
+        };
+
+        if let Some(tonemapper) = &mut self.tonemapper {
+            tonemapper.apply(rendering_device, source_rid, self.capture_rid, size, settings);
+        }
+    }
+
+    fn update_capture_texture(&mut self, rendering_device: &mut Gd<RenderingDevice>, source_rid: Rid, size: Vector2i) {
+        let Some(mut format) = rendering_device.texture_get_format(source_rid) else {
+            diagnostics::error(ErrorCode::InvalidTexture, "Unable to obtain the color buffer format.");
+            return;
+        };
+
+        // Tonemapped captures are written by a compute pass, other captures are copies of the color buffer.
+        let (data_format, usage) = if self.stage == SpoutCaptureStage::PostTonemap {
+            (DataFormat::R8G8B8A8_UNORM, TextureUsageBits::STORAGE_BIT)
+        } else {
+            (format.get_format(), TextureUsageBits::COLOR_ATTACHMENT_BIT)
+        };
+
+        if self.capture_rid.is_valid() && self.capture_size == size && self.capture_format == data_format {
+            return;
+        }
+
+        // Senders look the capture up when the frame is sent on the rendering thread, where this runs as well, so the
+        // previous capture is never sent after it was freed.
+        self.free_capture_texture(rendering_device);
+
+        // Copying from the capture lets senders without a native backend read it back.
+        format.set_format(data_format);
+        format.set_mipmaps(1);
+        format.set_samples(TextureSamples::SAMPLES_1);
+        format.set_usage_bits(
+            usage
+                | TextureUsageBits::SAMPLING_BIT
+                | TextureUsageBits::CAN_COPY_TO_BIT
+                | TextureUsageBits::CAN_COPY_FROM_BIT,
+        );
+
+        self.capture_rid = rendering_device.texture_create(&format, &RdTextureView::new_gd());
+        self.capture_size = size;
+        self.capture_format = data_format;
+        self.share_capture_rid();
+    }
+
+    fn free_capture_texture(&mut self, rendering_device: &mut Gd<RenderingDevice>) {
+        if self.capture_rid.is_valid() {
+            let capture_rid = std::mem::replace(&mut self.capture_rid, Rid::Invalid);
+            self.share_capture_rid();
+            rendering_device.free_rid(capture_rid);
+        }
+    }
+
+    fn share_capture_rid(&self) {
+        if let Ok(mut shared_capture_rid) = self.shared_capture_rid.lock() {
+            *shared_capture_rid = self.capture_rid;
+        }
+    }
+}
@@ -1,3 +1,9 @@
+mod camera_feed;
+mod compositor_effect;
 mod receiver;
+mod video_stream;
 
+pub use camera_feed::*;
+pub use compositor_effect::*;
 pub use receiver::*;
+pub use video_stream::*;
@@ -1,6 +1,77 @@
-use crate::spout::receiver::{SpoutReceiver, create_receiver};
-use godot::classes::{ITexture2D, RenderingServer, Texture2D};
+use crate::diagnostics;
+use crate::diagnostics::{Diagnostics, ErrorCode};
+use crate::manager::{EndpointId, SpoutManager};
+use crate::resource::SpoutCameraFeed;
+use crate::settings;
+use crate::spout::receiver::{ConversionSettings, Readback, ReceiverHandle, ReceiverStatus, image_data};
+use godot::classes::image::Format as ImageFormat;
+use godot::classes::notify::ObjectNotification;
+use godot::classes::rendering_device::{DataFormat, TextureUsageBits};
+use godot::classes::{CameraFeed, CameraServer, Engine, ITexture2D, Image, RenderingServer, Texture2D, Time};
+use godot::global::{Error, PropertyHint, PropertyUsageFlags};
+use godot::meta::{PropertyHintInfo, PropertyInfo};
+use godot::obj::{EngineBitfield, EngineEnum};
 use godot::prelude::*;
+use std::cell::{Cell, RefCell};
+
+const SENDER_WARNING_PROPERTY: &str = "sender_warning";
+const USED_TIMEOUT_MSEC: u64 = 1000;
+
+// An empty sender name in `ByName` mode follows the active sender, like Spout itself does.
+#[derive(GodotConvert, Var, Export, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[godot(via = i64)]
+pub enum SpoutConnectMode {
+    #[default]
+    ByName,
+    ActiveSender,
+    FirstAvailable,
+    MostRecent,
+}
+
+// Godot can't tell whether a texture is sampled, so `WhenUsed` treats a texture as used while nodes drawing it listen to
+// its changes, or for a second after it was drawn, read or passed to `mark_used()`. Materials only look the texture up
+// when they change, so scripts showing it through a material call `mark_used()` every frame or use `Always`.
+#[derive(GodotConvert, Var, Export, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[godot(via = i64)]
+pub enum SpoutUpdateMode {
+    #[default]
+    WhenUsed,
+    Always,
+    Manual,
+    Paused,
+}
+
+#[derive(GodotConvert, Var, Export, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[godot(via = i64)]
+pub enum SpoutDisconnectPolicy {
+    #[default]
+    HoldLastFrame,
+    ShowFallback,
+    Transparent,
+    Black,
+}
+
+// `Rgba8Srgb` stores gamma encoded colors, like imported color textures, the other formats hold linear colors.
+#[derive(GodotConvert, Var, Export, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[godot(via = i64)]
+pub enum SpoutTargetFormat {
+    #[default]
+    Keep,
+    Rgba8Srgb,
+    Rgba8Linear,
+    Rgba16F,
+}
+
+impl SpoutTargetFormat {
+    fn data_format(self) -> Option<DataFormat> {
+        match self {
+            SpoutTargetFormat::Keep => None,
+            SpoutTargetFormat::Rgba8Srgb => Some(DataFormat::R8G8B8A8_SRGB),
+            SpoutTargetFormat::Rgba8Linear => Some(DataFormat::R8G8B8A8_UNORM),
+            SpoutTargetFormat::Rgba16F => Some(DataFormat::R16G16B16A16_SFLOAT),
+        }
+    }
+}
 
 #[derive(GodotClass)]
 #[class(tool, base=Texture2D)]
@@ -8,73 +79,746 @@ pub struct SpoutReceiverTexture {
     #[var(get = get_sender_name, set = set_sender_name)]
     #[export]
     sender_name: GString,
-    spout_receiver: Box<dyn SpoutReceiver>,
-    pre_draw_callback: Option<Callable>,
+    #[var(get = get_connect_mode, set = set_connect_mode)]
+    #[export]
+    connect_mode: SpoutConnectMode,
+    #[var(set = set_target_format)]
+    #[export]
+    target_format: SpoutTargetFormat,
+    #[var(set = set_generate_mipmaps)]
+    #[export]
+    generate_mipmaps: bool,
+    // Frames stay untouched for this many frames after they stopped being shown, `0` copies every frame into the texture
+    // that is shown.
+    #[var(set = set_ring_depth)]
+    #[export(range = (0.0, 4.0, 1.0))]
+    ring_depth: i32,
+    #[var(set = set_texture_usage)]
+    #[export(flags = (
+        Sampling = 1,
+        ColorAttachment = 2,
+        Storage = 8,
+        StorageAtomic = 16,
+        CanUpdate = 64,
+        CanCopyFrom = 128,
+        CanCopyTo = 256
+    ))]
+    texture_usage: i64,
+    #[var]
+    #[export]
+    update_mode: SpoutUpdateMode,
+    #[var(set = set_on_disconnect)]
+    #[export]
+    on_disconnect: SpoutDisconnectPolicy,
+    #[var]
+    #[export(range = (0.0, 60.0, 0.1, or_greater, suffix = "s"))]
+    disconnect_timeout: f64,
+    #[var(set = set_no_signal_texture)]
+    #[export]
+    no_signal_texture: Option<Gd<Texture2D>>,
+    #[var(set = set_fallback_size)]
+    #[export]
+    fallback_size: Vector2i,
+    #[var(set = set_expose_as_camera_feed)]
+    #[export]
+    expose_as_camera_feed: bool,
+    // Receives every logged diagnostic as a dictionary instead of the Godot output.
+    #[var(set = set_log_sink)]
+    log_sink: Callable,
+    #[var(get = is_sender_connected)]
+    connected: bool,
+    status: ReceiverStatus,
+    frame_size: Vector2i,
+    showing_fallback: bool,
+    disconnected_since_msec: Option<u64>,
+    last_used_msec: Cell<Option<u64>>,
+    cached_image: RefCell<FrameCache<Gd<Image>>>,
+    pending_images: Vec<(i64, Gd<Image>)>,
+    // The frames shown when images were requested, each satisfied by an image of that frame or a later one.
+    requested_frames: Vec<i64>,
+    last_read_frame: Option<i64>,
+    camera_feed: Option<Gd<SpoutCameraFeed>>,
+    spout_receiver: Option<ReceiverHandle>,
+    endpoint: Option<EndpointId>,
     base: Base<Texture2D>,
 }
 
 impl Drop for SpoutReceiverTexture {
     fn drop(&mut self) {
-        if let Some(callback) = self.pre_draw_callback.take() {
-            RenderingServer::singleton().disconnect("frame_pre_draw", &callback);
+        if let (Some(id), Some(mut manager)) = (self.endpoint.take(), SpoutManager::singleton()) {
+            manager.bind_mut().unregister_receiver(id);
         }
+
+        self.remove_camera_feed();
     }
 }
 
 #[godot_api]
 impl ITexture2D for SpoutReceiverTexture {
     fn init(base: Base<Texture2D>) -> SpoutReceiverTexture {
-        let driver_name = RenderingServer::singleton()
-            .get_current_rendering_driver_name()
-            .to_string();
-
         Self {
             sender_name: GString::new(),
-            spout_receiver: create_receiver(&driver_name),
-            pre_draw_callback: None,
+            connect_mode: SpoutConnectMode::default(),
+            target_format: SpoutTargetFormat::default(),
+            generate_mipmaps: false,
+            ring_depth: 1,
+            update_mode: settings::get().default_update_mode,
+            texture_usage: (TextureUsageBits::SAMPLING_BIT | TextureUsageBits::CAN_COPY_FROM_BIT).ord() as i64,
+            on_disconnect: SpoutDisconnectPolicy::default(),
+            disconnect_timeout: 0.0,
+            no_signal_texture: None,
+            fallback_size: Vector2i::new(1920, 1080),
+            expose_as_camera_feed: false,
+            log_sink: Callable::invalid(),
+            connected: false,
+            status: ReceiverStatus::default(),
+            frame_size: Vector2i::ZERO,
+            showing_fallback: false,
+            disconnected_since_msec: None,
+            last_used_msec: Cell::new(None),
+            cached_image: RefCell::new(FrameCache::default()),
+            pending_images: Vec::new(),
+            requested_frames: Vec::new(),
+            last_read_frame: None,
+            camera_feed: None,
+            spout_receiver: None,
+            endpoint: None,
             base,
         }
     }
 
+    fn on_notification(&mut self, what: ObjectNotification) {
+        if what != ObjectNotification::POSTINITIALIZE {
+            return;
+        }
+
+        let Some(mut manager) = SpoutManager::singleton() else {
+            return;
+        };
+
+        let (id, spout_receiver) = manager.bind_mut().register_receiver(self.base().instance_id());
+        self.endpoint = Some(id);
+        self.spout_receiver = Some(spout_receiver);
+        self.update_conversion();
+    }
+
+    fn validate_property(&self, property: &mut PropertyInfo) {
+        if property.property_name.to_string() != "sender_name" {
+            return;
+        }
+
+        let Some(manager) = SpoutManager::singleton() else {
+            return;
+        };
+
+        // Suggestions still allow free text, for senders that are not running yet.
+        property.hint_info = PropertyHintInfo {
+            hint: PropertyHint::ENUM_SUGGESTION,
+            hint_string: manager.bind().discovered_senders().join(",").into(),
+        };
+    }
+
+    fn get_property_list(&mut self) -> Vec<PropertyInfo> {
+        if !Engine::singleton().is_editor_hint() || !self.is_sender_missing() {
+            return Vec::new();
+        }
+
+        let mut warning = PropertyInfo::new_var::<GString>(SENDER_WARNING_PROPERTY);
+        warning.usage = PropertyUsageFlags::EDITOR | PropertyUsageFlags::READ_ONLY;
+
+        vec![warning]
+    }
+
+    fn get_property(&self, property: StringName) -> Option<Variant> {
+        if property.to_string() != SENDER_WARNING_PROPERTY {
+            return None;
+        }
+
+        Some(format!("Sender \"{}\" is not currently running.", self.sender_name).to_variant())
+    }
+
     fn get_width(&self) -> i32 {
-        self.spout_receiver.width()
+        self.mark_used();
+        self.size().x
     }
 
     fn get_height(&self) -> i32 {
-        self.spout_receiver.height()
+        self.mark_used();
+        self.size().y
     }
 
     fn get_rid(&self) -> Rid {
-        self.spout_receiver.rid()
+        self.mark_used();
+        self.spout_receiver.as_ref().map_or(Rid::Invalid, ReceiverHandle::rid)
     }
 }
 
 #[godot_api]
 impl SpoutReceiverTexture {
+    #[signal]
+    fn sender_connected(info: Dictionary);
+
+    #[signal]
+    fn sender_disconnected();
+
+    #[signal]
+    fn size_changed(old_size: Vector2i, new_size: Vector2i);
+
+    #[signal]
+    fn format_changed();
+
+    #[signal]
+    fn image_received(image: Gd<Image>);
+
+    #[func]
+    fn is_sender_connected(&self) -> bool {
+        self.connected
+    }
+
     #[func]
     fn get_sender_name(&self) -> GString {
         self.sender_name.clone()
     }
 
     #[func]
-    fn set_sender_name(&mut self, sender_name: GString) {
-        if let Some(callback) = self.pre_draw_callback.take() {
-            RenderingServer::singleton().disconnect("frame_pre_draw", &callback);
+    pub(crate) fn set_sender_name(&mut self, sender_name: GString) {
+        if let (Some(id), Some(mut manager)) = (self.endpoint, SpoutManager::singleton()) {
+            manager
+                .bind_mut()
+                .set_receiver_sender_name(id, &sender_name.to_string());
         }
 
-        let callable = self.base().callable("on_pre_draw");
-        RenderingServer::singleton().connect("frame_pre_draw", &callable);
-
-        self.spout_receiver.set_sender_name(&sender_name.to_string());
         self.sender_name = sender_name;
-        self.pre_draw_callback = Some(callable);
         self.base_mut().emit_changed();
+        self.base_mut().notify_property_list_changed();
+    }
+
+    #[func]
+    fn get_connect_mode(&self) -> SpoutConnectMode {
+        self.connect_mode
+    }
+
+    #[func]
+    pub(crate) fn set_connect_mode(&mut self, connect_mode: SpoutConnectMode) {
+        if let (Some(id), Some(mut manager)) = (self.endpoint, SpoutManager::singleton()) {
+            manager.bind_mut().set_receiver_connect_mode(id, connect_mode);
+        }
+
+        self.connect_mode = connect_mode;
+        self.base_mut().emit_changed();
+        self.base_mut().notify_property_list_changed();
+    }
+
+    #[func]
+    fn set_target_format(&mut self, target_format: SpoutTargetFormat) {
+        self.target_format = target_format;
+        self.update_conversion();
     }
 
     #[func]
-    fn on_pre_draw(&mut self) {
-        if self.spout_receiver.update_resource() {
+    fn set_generate_mipmaps(&mut self, generate_mipmaps: bool) {
+        self.generate_mipmaps = generate_mipmaps;
+        self.update_conversion();
+    }
+
+    #[func]
+    fn set_ring_depth(&mut self, ring_depth: i32) {
+        self.ring_depth = ring_depth.clamp(0, 4);
+        self.update_conversion();
+    }
+
+    // Frames are copied into the receiver texture, which is created with this usage.
+    #[func]
+    fn set_texture_usage(&mut self, texture_usage: i64) {
+        self.texture_usage = texture_usage;
+        self.update_conversion();
+    }
+
+    // The texture is recreated when the sender changes its size or format, so it shouldn't be held on to.
+    #[func]
+    fn get_rd_texture(&self) -> Rid {
+        self.mark_used();
+
+        let Some(spout_receiver) = &self.spout_receiver else {
+            return Rid::Invalid;
+        };
+
+        RenderingServer::singleton().texture_get_rd_texture(spout_receiver.rid())
+    }
+
+    // Returns a copy, so callers are free to modify it.
+    #[func]
+    fn get_image(&self) -> Option<Gd<Image>> {
+        let mut image = Image::new_gd();
+        image.copy_from(&self.current_image()?);
+        Some(image)
+    }
+
+    #[func]
+    fn set_log_sink(&mut self, log_sink: Callable) {
+        SpoutManager::update_log_sink(self.endpoint, &log_sink);
+        self.log_sink = log_sink;
+    }
+
+    #[func]
+    fn get_last_error(&self) -> Dictionary {
+        self.diagnostics()
+            .map_or_else(Dictionary::new, Diagnostics::last_error_dictionary)
+    }
+
+    #[func]
+    fn clear_last_error(&self) {
+        self.diagnostics().into_iter().for_each(Diagnostics::clear_last_error);
+    }
+
+    // Keeps the receiver updating for a second in the `WhenUsed` update mode.
+    #[func]
+    pub fn mark_used(&self) {
+        self.last_used_msec.set(Some(Time::singleton().get_ticks_msec()));
+    }
+
+    // Updates the receiver once, for the `Manual` update mode.
+    #[func]
+    pub(crate) fn poll(&self) {
+        if let (Some(id), Some(manager)) = (self.endpoint, SpoutManager::singleton()) {
+            manager.bind().poll_receiver(id);
+        }
+    }
+
+    #[func]
+    fn request_image(&mut self) {
+        let frame = self.status.frame;
+        self.requested_frames.push(frame);
+        self.read_image();
+    }
+
+    #[func]
+    fn get_camera_feed(&self) -> Option<Gd<CameraFeed>> {
+        self.camera_feed.clone().map(Gd::upcast)
+    }
+
+    // Only the first call after a frame arrived reads the texture back, later ones use the same image.
+    #[func]
+    fn get_pixel(&self, x: i32, y: i32) -> Color {
+        self.current_image()
+            .map(|image| image.get_pixel(x, y))
+            .unwrap_or_default()
+    }
+
+    #[func]
+    fn save_png(&self, path: GString) -> Error {
+        let Some(image) = self.current_image() else {
+            return Error::ERR_UNAVAILABLE;
+        };
+
+        image.save_png(&path)
+    }
+
+    #[func]
+    fn set_expose_as_camera_feed(&mut self, expose_as_camera_feed: bool) {
+        self.expose_as_camera_feed = expose_as_camera_feed;
+
+        if expose_as_camera_feed {
+            self.add_camera_feed();
+        } else {
+            self.remove_camera_feed();
+        }
+    }
+
+    #[func]
+    fn set_on_disconnect(&mut self, on_disconnect: SpoutDisconnectPolicy) {
+        self.on_disconnect = on_disconnect;
+        self.refresh_fallback();
+    }
+
+    #[func]
+    fn set_no_signal_texture(&mut self, no_signal_texture: Option<Gd<Texture2D>>) {
+        self.no_signal_texture = no_signal_texture;
+        self.refresh_fallback();
+    }
+
+    #[func]
+    fn set_fallback_size(&mut self, fallback_size: Vector2i) {
+        self.fallback_size = fallback_size.coord_max(Vector2i::ONE);
+        self.refresh_fallback();
+    }
+}
+
+impl SpoutReceiverTexture {
+    pub(crate) fn status(&self) -> &ReceiverStatus {
+        &self.status
+    }
+
+    // Called by the manager after every poll of the backend, `updated` is set when the backend produced a new resource.
+    // Returns whether the backend should be updated on the next poll.
+    pub(crate) fn on_receiver_polled(&mut self, updated: bool) -> bool {
+        let Some(status) = self.spout_receiver.as_ref().map(ReceiverHandle::status) else {
+            return false;
+        };
+
+        let old_size = self.size();
+        let previous = std::mem::replace(&mut self.status, status.clone());
+        self.connected = status.connected;
+
+        if status.connected != previous.connected {
+            if status.connected {
+                let info = sender_info(&status);
+                self.base_mut().emit_signal("sender_connected", &[info.to_variant()]);
+            } else {
+                self.base_mut().emit_signal("sender_disconnected", &[]);
+            }
+        }
+
+        if updated {
+            self.frame_size = Vector2i::new(status.width, status.height);
+            self.showing_fallback = false;
+        }
+
+        if status.connected {
+            self.disconnected_since_msec = None;
+        } else {
+            self.update_disconnected();
+        }
+
+        let new_size = self.size();
+
+        if old_size != new_size {
+            self.base_mut()
+                .emit_signal("size_changed", &[old_size.to_variant(), new_size.to_variant()]);
+        }
+
+        if status.format != previous.format {
+            self.base_mut().emit_signal("format_changed", &[]);
+        }
+
+        if updated {
+            self.cached_image.borrow_mut().clear();
             self.base_mut().emit_changed();
         }
+
+        let (readbacks, receiver_diagnostics) = self
+            .spout_receiver
+            .as_ref()
+            .map(|spout_receiver| (spout_receiver.take_readbacks(), spout_receiver.diagnostics().clone()))
+            .unwrap_or_default();
+
+        let images = diagnostics::with_endpoint(&receiver_diagnostics, || {
+            readbacks
+                .into_iter()
+                .filter_map(|readback| {
+                    let frame = readback.frame;
+                    readback_image(readback).map(|image| (frame, image))
+                })
+                .collect::<Vec<_>>()
+        });
+
+        self.pending_images.extend(images);
+
+        for (frame, image) in std::mem::take(&mut self.pending_images) {
+            self.deliver_image(frame, image);
+        }
+
+        if status.sender_name != previous.sender_name {
+            self.update_camera_feed_name();
+        }
+
+        // Camera feeds only take images, so new frames are read back while someone is watching.
+        let feed_wants_frame =
+            status.connected && self.is_camera_feed_active() && self.last_read_frame != Some(status.frame);
+
+        if feed_wants_frame || !self.requested_frames.is_empty() {
+            self.read_image();
+        }
+
+        match self.update_mode {
+            SpoutUpdateMode::WhenUsed => self.is_used() || self.is_camera_feed_active(),
+            SpoutUpdateMode::Always => true,
+            SpoutUpdateMode::Manual | SpoutUpdateMode::Paused => false,
+        }
+    }
+
+    // Reading the texture back waits for the GPU, so the image is kept until another frame is received.
+    fn current_image(&self) -> Option<Gd<Image>> {
+        self.mark_used();
+
+        let spout_receiver = self.spout_receiver.as_ref()?;
+        let received_frames = spout_receiver.status().received_frames;
+
+        self.cached_image.borrow_mut().get_or_insert_with(received_frames, || {
+            RenderingServer::singleton().texture_2d_get(spout_receiver.rid())
+        })
+    }
+
+    fn is_used(&self) -> bool {
+        let now = Time::singleton().get_ticks_msec();
+
+        if self
+            .last_used_msec
+            .get()
+            .is_some_and(|last| now.saturating_sub(last) < USED_TIMEOUT_MSEC)
+        {
+            return true;
+        }
+
+        // Sprites and controls drawing the texture listen to its changes.
+        !self.base().get_signal_connection_list("changed").is_empty()
+    }
+
+    fn diagnostics(&self) -> Option<&Diagnostics> {
+        self.spout_receiver.as_ref().map(ReceiverHandle::diagnostics)
+    }
+
+    fn update_conversion(&self) {
+        if let Some(spout_receiver) = &self.spout_receiver {
+            spout_receiver.set_conversion(ConversionSettings {
+                target_format: self.target_format.data_format(),
+                generate_mipmaps: self.generate_mipmaps,
+                ring_depth: self.ring_depth as usize,
+                usage: TextureUsageBits::from_ord(self.texture_usage as u64),
+            });
+        }
+    }
+
+    fn read_image(&mut self) {
+        let Some(spout_receiver) = &self.spout_receiver else {
+            return;
+        };
+
+        // One readback at a time, requests made meanwhile are served by a later one if this one is too old.
+        if spout_receiver.is_readback_pending() {
+            return;
+        }
+
+        let frame = self.status.frame;
+        self.last_read_frame = Some(frame);
+
+        if spout_receiver.request_readback() {
+            return;
+        }
+
+        // Without a rendering device the image is read synchronously, but still delivered on the next poll.
+        if let Some(image) = self.get_image() {
+            self.pending_images.push((frame, image));
+        }
+    }
+
+    fn deliver_image(&mut self, frame: i64, image: Gd<Image>) {
+        if let Some(camera_feed) = &mut self.camera_feed {
+            if camera_feed.is_active() {
+                camera_feed.set_rgb_image(&image);
+            }
+        }
+
+        let requests = self.requested_frames.len();
+        self.requested_frames.retain(|&requested_frame| requested_frame > frame);
+
+        if self.requested_frames.len() < requests {
+            self.base_mut().emit_signal("image_received", &[image.to_variant()]);
+        }
+    }
+
+    fn is_camera_feed_active(&self) -> bool {
+        self.camera_feed
+            .as_ref()
+            .is_some_and(|camera_feed| camera_feed.is_active())
+    }
+
+    fn add_camera_feed(&mut self) {
+        if self.camera_feed.is_some() {
+            return;
+        }
+
+        let camera_feed = SpoutCameraFeed::new_gd();
+        CameraServer::singleton().add_feed(&camera_feed);
+        self.camera_feed = Some(camera_feed);
+        self.update_camera_feed_name();
+    }
+
+    fn remove_camera_feed(&mut self) {
+        if let Some(camera_feed) = self.camera_feed.take() {
+            CameraServer::singleton().remove_feed(&camera_feed);
+        }
+    }
+
+    fn update_camera_feed_name(&mut self) {
+        let name = match self.status.sender_name.as_str() {
+            "" => "Spout".to_owned(),
+            sender_name => format!("Spout: {sender_name}"),
+        };
+
+        if let Some(camera_feed) = &mut self.camera_feed {
+            camera_feed.set_name(&name);
+        }
+    }
+
+    fn size(&self) -> Vector2i {
+        if self.showing_fallback || self.frame_size == Vector2i::ZERO {
+            self.fallback_size
+        } else {
+            self.frame_size
+        }
+    }
+
+    fn update_disconnected(&mut self) {
+        if self.showing_fallback {
+            return;
+        }
+
+        let has_frame = self.frame_size != Vector2i::ZERO;
+
+        if has_frame && self.on_disconnect == SpoutDisconnectPolicy::HoldLastFrame {
+            return;
+        }
+
+        let now = Time::singleton().get_ticks_msec();
+        let disconnected_since_msec = *self.disconnected_since_msec.get_or_insert(now);
+        let elapsed = now.saturating_sub(disconnected_since_msec) as f64 / 1000.0;
+
+        // Without a frame to hold there is nothing to wait for, so the fallback is shown right away.
+        if has_frame && elapsed < self.disconnect_timeout {
+            return;
+        }
+
+        self.show_fallback();
+    }
+
+    fn refresh_fallback(&mut self) {
+        if self.showing_fallback {
+            self.show_fallback();
+        }
+    }
+
+    fn show_fallback(&mut self) {
+        let Some(spout_receiver) = &self.spout_receiver else {
+            return;
+        };
+
+        let image = self.fallback_image();
+        let texture_rid = RenderingServer::singleton().texture_2d_create(&image);
+        spout_receiver.replace_texture(texture_rid);
+
+        self.showing_fallback = true;
+        self.frame_size = Vector2i::ZERO;
+        self.cached_image.borrow_mut().clear();
+        self.base_mut().emit_changed();
+    }
+
+    // The fallback image is stretched to `fallback_size` when drawn, so plain colors only need a single pixel.
+    fn fallback_image(&self) -> Gd<Image> {
+        if self.on_disconnect == SpoutDisconnectPolicy::ShowFallback {
+            if let Some(image) = self.no_signal_texture.as_ref().and_then(|texture| texture.get_image()) {
+                return image;
+            }
+        }
+
+        let color = match self.on_disconnect {
+            SpoutDisconnectPolicy::Transparent => Color::TRANSPARENT_BLACK,
+            _ => Color::BLACK,
+        };
+
+        let mut image = Image::create_empty(1, 1, false, ImageFormat::RGBA8).expect("1x1 image should be valid");
+        image.fill(color);
+        image
+    }
+
+    fn is_sender_missing(&self) -> bool {
+        if self.connect_mode != SpoutConnectMode::ByName || self.sender_name.is_empty() {
+            return false;
+        }
+
+        SpoutManager::singleton()
+            .is_some_and(|manager| !manager.bind().is_sender_discovered(&self.sender_name.to_string()))
+    }
+}
+
+fn sender_info(status: &ReceiverStatus) -> Dictionary {
+    dict! {
+        "name": status.sender_name.as_str(),
+        "width": status.width,
+        "height": status.height,
+        "format": status.format.ord(),
+        "frame": status.frame,
+    }
+}
+
+// Keeps a value computed from the receiver texture for as long as it shows the same received frame.
+struct FrameCache<T> {
+    cached: Option<(u64, T)>,
+}
+
+impl<T> Default for FrameCache<T> {
+    fn default() -> Self {
+        Self { cached: None }
+    }
+}
+
+impl<T: Clone> FrameCache<T> {
+    fn get_or_insert_with(&mut self, received_frames: u64, f: impl FnOnce() -> Option<T>) -> Option<T> {
+        let cached = self.cached.as_ref();
+
+        if let Some((_, value)) = cached.filter(|(cached_frames, _)| *cached_frames == received_frames) {
+            return Some(value.clone());
+        }
+
+        let value = f()?;
+        self.cached = Some((received_frames, value.clone()));
+        Some(value)
+    }
+
+    fn clear(&mut self) {
+        self.cached = None;
+    }
+}
+
+fn readback_image(readback: Readback) -> Option<Gd<Image>> {
+    let Some((format, data)) = image_data(readback.format, &readback.data) else {
+        diagnostics::error(
+            ErrorCode::UnsupportedFormat,
+            format!("Unsupported texture format for readback: {:?}", readback.format),
+        );
+        return None;
+    };
+
+    let data = PackedByteArray::from(data.as_ref());
+    Image::create_from_data(readback.width, readback.height, false, format, &data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_cache_is_reused_for_the_same_frame() {
+        let mut cache = FrameCache::default();
+
+        assert_eq!(cache.get_or_insert_with(1, || Some("first")), Some("first"));
+        assert_eq!(cache.get_or_insert_with(1, || Some("second")), Some("first"));
+    }
+
+    #[test]
+    fn frame_cache_is_refreshed_after_a_frame_was_received() {
+        let mut cache = FrameCache::default();
+
+        assert_eq!(cache.get_or_insert_with(1, || Some("first")), Some("first"));
+        assert_eq!(cache.get_or_insert_with(2, || Some("second")), Some("second"));
+        assert_eq!(cache.get_or_insert_with(2, || Some("third")), Some("second"));
+    }
+
+    #[test]
+    fn frame_cache_is_refreshed_after_clearing() {
+        let mut cache = FrameCache::default();
+
+        cache.get_or_insert_with(1, || Some("first"));
+        cache.clear();
+
+        assert_eq!(cache.get_or_insert_with(1, || Some("second")), Some("second"));
+    }
+
+    #[test]
+    fn failed_reads_are_not_cached() {
+        let mut cache = FrameCache::default();
+
+        assert_eq!(cache.get_or_insert_with(1, || None), None);
+        assert_eq!(cache.get_or_insert_with(1, || Some("first")), Some("first"));
     }
 }
@@ -0,0 +1,127 @@
+use godot::classes::{IVideoStream, IVideoStreamPlayback, Texture2D, VideoStream, VideoStreamPlayback};
+use godot::prelude::*;
+
+use crate::resource::{SpoutConnectMode, SpoutReceiverTexture, SpoutUpdateMode};
+
+#[derive(GodotClass)]
+#[class(tool, init, base=VideoStream)]
+pub struct SpoutVideoStream {
+    #[var]
+    #[export]
+    sender_name: GString,
+    #[var]
+    #[export]
+    connect_mode: SpoutConnectMode,
+    base: Base<VideoStream>,
+}
+
+#[godot_api]
+impl IVideoStream for SpoutVideoStream {
+    fn instantiate_playback(&mut self) -> Option<Gd<VideoStreamPlayback>> {
+        let mut texture = SpoutReceiverTexture::new_gd();
+
+        {
+            let mut receiver = texture.bind_mut();
+            receiver.set_sender_name(self.sender_name.clone());
+            receiver.set_connect_mode(self.connect_mode);
+            // Frames are only received while the player is playing.
+            receiver.set_update_mode(SpoutUpdateMode::Paused);
+        }
+
+        let playback = Gd::from_init_fn(|base| SpoutVideoStreamPlayback {
+            texture,
+            playing: false,
+            paused: false,
+            position: 0.0,
+            base,
+        });
+
+        Some(playback.upcast())
+    }
+}
+
+// Live feeds have no length, so seeking is ignored and the position only counts the time spent playing.
+#[derive(GodotClass)]
+#[class(tool, no_init, base=VideoStreamPlayback)]
+pub struct SpoutVideoStreamPlayback {
+    texture: Gd<SpoutReceiverTexture>,
+    playing: bool,
+    paused: bool,
+    position: f64,
+    base: Base<VideoStreamPlayback>,
+}
+
+#[godot_api]
+impl IVideoStreamPlayback for SpoutVideoStreamPlayback {
+    fn play(&mut self) {
+        self.playing = true;
+        self.paused = false;
+        self.update_receiver();
+    }
+
+    fn stop(&mut self) {
+        self.playing = false;
+        self.paused = false;
+        self.position = 0.0;
+        self.update_receiver();
+    }
+
+    fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        self.update_receiver();
+    }
+
+    fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    fn get_length(&self) -> f64 {
+        0.0
+    }
+
+    fn get_playback_position(&self) -> f64 {
+        self.position
+    }
+
+    fn seek(&mut self, _time: f64) {
+        // Live feeds can't be seeked.
+    }
+
+    fn set_audio_track(&mut self, _idx: i32) {
+        // Spout carries no audio.
+    }
+
+    fn get_texture(&self) -> Option<Gd<Texture2D>> {
+        Some(self.texture.clone().upcast())
+    }
+
+    fn update(&mut self, delta: f64) {
+        if self.playing && !self.paused {
+            self.position += delta;
+        }
+    }
+
+    fn get_channels(&self) -> i32 {
+        0
+    }
+
+    fn get_mix_rate(&self) -> i32 {
+        0
+    }
+}
+
+impl SpoutVideoStreamPlayback {
+    fn update_receiver(&mut self) {
+        let update_mode = if self.playing && !self.paused {
+            SpoutUpdateMode::Always
+        } else {
+            SpoutUpdateMode::Paused
+        };
+
+        self.texture.bind_mut().set_update_mode(update_mode);
+    }
+}
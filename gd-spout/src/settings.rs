@@ -0,0 +1,180 @@
+use godot::classes::{Engine, ProjectSettings};
+use godot::global::PropertyHint;
+use godot::obj::EngineEnum;
+use godot::prelude::*;
+use std::sync::RwLock;
+
+use crate::resource::SpoutUpdateMode;
+
+const PREFERRED_BACKEND: &str = "gd_spout/backend/preferred_backend";
+const SENDER_NAME_PREFIX: &str = "gd_spout/senders/name_prefix";
+const DEFAULT_UPDATE_MODE: &str = "gd_spout/receivers/default_update_mode";
+const SEND_IN_EDITOR: &str = "gd_spout/editor/send_in_editor";
+const RECEIVE_IN_EDITOR: &str = "gd_spout/editor/receive_in_editor";
+const LOG_VERBOSITY: &str = "gd_spout/debug/log_verbosity";
+
+const AUTOMATIC_BACKEND: &str = "auto";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogVerbosity {
+    Errors,
+    #[default]
+    Warnings,
+    Verbose,
+}
+
+impl LogVerbosity {
+    fn from_ord(ord: i64) -> Self {
+        match ord {
+            0 => LogVerbosity::Errors,
+            2 => LogVerbosity::Verbose,
+            _ => LogVerbosity::Warnings,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Settings {
+    pub preferred_backend: Option<String>,
+    pub sender_name_prefix: String,
+    pub default_update_mode: SpoutUpdateMode,
+    pub send_in_editor: bool,
+    pub receive_in_editor: bool,
+    pub log_verbosity: LogVerbosity,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            preferred_backend: None,
+            sender_name_prefix: String::new(),
+            default_update_mode: SpoutUpdateMode::default(),
+            send_in_editor: false,
+            receive_in_editor: true,
+            log_verbosity: LogVerbosity::default(),
+        }
+    }
+}
+
+// Settings are read once on the main thread and cached, as they are also needed on the rendering thread.
+static SETTINGS: RwLock<Option<Settings>> = RwLock::new(None);
+
+pub fn register() {
+    let mut project_settings = ProjectSettings::singleton();
+
+    define(
+        &mut project_settings,
+        PREFERRED_BACKEND,
+        AUTOMATIC_BACKEND.to_variant(),
+        "auto,dx12,cpu",
+    );
+    define(&mut project_settings, SENDER_NAME_PREFIX, "".to_variant(), "");
+    define(
+        &mut project_settings,
+        DEFAULT_UPDATE_MODE,
+        SpoutUpdateMode::default().to_variant(),
+        "When Used,Always,Manual,Paused",
+    );
+    define(&mut project_settings, SEND_IN_EDITOR, false.to_variant(), "");
+    define(&mut project_settings, RECEIVE_IN_EDITOR, true.to_variant(), "");
+    define(
+        &mut project_settings,
+        LOG_VERBOSITY,
+        (LogVerbosity::default() as i64).to_variant(),
+        "Errors,Warnings,Verbose",
+    );
+
+    load();
+
+    // Keeps the cache current while the settings are edited in the editor.
+    let callable = Callable::from_sync_fn("gd_spout_settings_changed", |_args| {
+        load();
+        Ok(Variant::nil())
+    });
+
+    project_settings.connect("settings_changed", &callable);
+}
+
+pub fn get() -> Settings {
+    SETTINGS
+        .read()
+        .ok()
+        .and_then(|settings| settings.clone())
+        .unwrap_or_default()
+}
+
+// Editor settings take effect for senders and receivers created afterwards.
+pub fn is_sending_enabled() -> bool {
+    !Engine::singleton().is_editor_hint() || get().send_in_editor
+}
+
+pub fn is_receiving_enabled() -> bool {
+    !Engine::singleton().is_editor_hint() || get().receive_in_editor
+}
+
+// Settings with enum options get an enum hint, everything else is edited as its type.
+fn define(project_settings: &mut Gd<ProjectSettings>, name: &str, default: Variant, enum_options: &str) {
+    if !project_settings.has_setting(name) {
+        project_settings.set_setting(name, &default);
+    }
+
+    let hint = if enum_options.is_empty() {
+        PropertyHint::NONE
+    } else {
+        PropertyHint::ENUM
+    };
+
+    project_settings.set_initial_value(name, &default);
+    project_settings.add_property_info(&dict! {
+        "name": name,
+        "type": default.get_type().ord(),
+        "hint": hint.ord(),
+        "hint_string": enum_options,
+    });
+}
+
+fn load() {
+    let project_settings = ProjectSettings::singleton();
+    // Overrides such as `gd_spout/backend/preferred_backend.windows` are picked up for the running platform.
+    let setting = |name: &str| project_settings.get_setting_with_override(name);
+
+    let preferred_backend = setting(PREFERRED_BACKEND)
+        .try_to::<GString>()
+        .unwrap_or_default()
+        .to_string();
+
+    let settings = Settings {
+        preferred_backend: Some(preferred_backend)
+            .filter(|backend| !backend.is_empty() && backend != AUTOMATIC_BACKEND),
+        sender_name_prefix: setting(SENDER_NAME_PREFIX)
+            .try_to::<GString>()
+            .unwrap_or_default()
+            .to_string(),
+        default_update_mode: setting(DEFAULT_UPDATE_MODE).try_to().unwrap_or_default(),
+        send_in_editor: setting(SEND_IN_EDITOR).try_to().unwrap_or(false),
+        receive_in_editor: setting(RECEIVE_IN_EDITOR).try_to().unwrap_or(true),
+        log_verbosity: LogVerbosity::from_ord(setting(LOG_VERBOSITY).try_to().unwrap_or(1)),
+    };
+
+    if let Ok(mut cached) = SETTINGS.write() {
+        *cached = Some(settings);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_verbosity_from_ord() {
+        assert_eq!(LogVerbosity::from_ord(0), LogVerbosity::Errors);
+        assert_eq!(LogVerbosity::from_ord(1), LogVerbosity::Warnings);
+        assert_eq!(LogVerbosity::from_ord(2), LogVerbosity::Verbose);
+    }
+
+    #[test]
+    fn unknown_log_verbosity_falls_back_on_warnings() {
+        assert_eq!(LogVerbosity::from_ord(-1), LogVerbosity::Warnings);
+        assert_eq!(LogVerbosity::from_ord(3), LogVerbosity::Warnings);
+    }
+}
@@ -1,5 +1,11 @@
 #[cfg(target_os = "windows")]
 mod d3d12_util;
 
+pub(crate) mod compute;
+pub(crate) mod discovery;
+pub(crate) mod frame_store;
 pub(crate) mod receiver;
+pub(crate) mod registry;
+pub(crate) mod render_thread;
 pub(crate) mod sender;
+pub(crate) mod tonemap;
@@ -0,0 +1,68 @@
+use crate::diagnostics;
+use crate::diagnostics::ErrorCode;
+use godot::classes::rendering_device::{ShaderLanguage, ShaderStage};
+use godot::classes::{RdShaderSource, RenderingDevice};
+use godot::prelude::*;
+
+// Compute shaders working on textures all use 8x8 workgroups.
+const WORKGROUP_SIZE: u32 = 8;
+
+const SRGB_FUNCTIONS: &str = r#"
+vec3 srgb_to_linear(vec3 color) {
+    return mix(color / 12.92, pow((color + 0.055) / 1.055, vec3(2.4)), greaterThan(color, vec3(0.04045)));
+}
+
+vec3 linear_to_srgb(vec3 color) {
+    return mix(color * 12.92, 1.055 * pow(color, vec3(1.0 / 2.4)) - 0.055, greaterThan(color, vec3(0.0031308)));
+}
+"#;
+
+// Compiles a compute shader, replacing `SRGB_FUNCTIONS` with the sRGB transfer functions and `IMAGE_FORMAT` with the
+// format of the written image.
+pub fn compile_shader(
+    rendering_device: &mut Gd<RenderingDevice>,
+    source: &str,
+    image_format: &str,
+    name: &str,
+) -> Option<Rid> {
+    let source = source
+        .replace("SRGB_FUNCTIONS", SRGB_FUNCTIONS)
+        .replace("IMAGE_FORMAT", image_format);
+
+    let mut shader_source = RdShaderSource::new_gd();
+    shader_source.set_language(ShaderLanguage::GLSL);
+    shader_source.set_stage_source(ShaderStage::COMPUTE, &source);
+
+    let spirv = rendering_device.shader_compile_spirv_from_source(&shader_source)?;
+    let compile_error = spirv.get_stage_compile_error(ShaderStage::COMPUTE);
+
+    if !compile_error.is_empty() {
+        diagnostics::error(
+            ErrorCode::ConversionFailed,
+            format!("Failed to compile {name} shader: {compile_error}"),
+        );
+        return None;
+    }
+
+    let shader_rid = rendering_device.shader_create_from_spirv(&spirv);
+
+    if !shader_rid.is_valid() {
+        diagnostics::error(ErrorCode::ConversionFailed, format!("Failed to create {name} shader."));
+        return None;
+    }
+
+    Some(shader_rid)
+}
+
+pub fn dispatch(rendering_device: &mut Gd<RenderingDevice>, compute_list: i64, size: Vector2i, params: &[i32]) {
+    let params: Vec<u8> = params.iter().flat_map(|value| value.to_ne_bytes()).collect();
+    let params = PackedByteArray::from(params.as_slice());
+
+    rendering_device.compute_list_set_push_constant(compute_list, &params, params.len() as u32);
+    rendering_device.compute_list_dispatch(
+        compute_list,
+        (size.x as u32).div_ceil(WORKGROUP_SIZE),
+        (size.y as u32).div_ceil(WORKGROUP_SIZE),
+        1,
+    );
+}
@@ -22,6 +22,15 @@ pub fn get_d3d12_device() -> Option<NonNull<ID3D12Device>> {
     NonNull::new(logical_device_id as *mut ID3D12Device)
 }
 
+// Only the device is probed, as the D3D12 rendering driver is required anyway and everything else comes with it.
+pub fn probe() -> Result<(), Box<dyn std::error::Error>> {
+    if get_d3d12_device().is_none() {
+        return Err("Unable to obtain D3D12 Device".into());
+    }
+
+    Ok(())
+}
+
 pub fn get_d3d12_command_queue() -> Option<NonNull<ID3D12CommandQueue>> {
     let mut device = RenderingServer::singleton().get_rendering_device()?;
     let command_queue_id = device.get_driver_resource(DriverResource::COMMAND_QUEUE, Rid::Invalid, 0);
@@ -40,21 +49,26 @@ pub fn convert_dxgi_to_rd_data_format(dxgi_input: DXGI_FORMAT) -> DataFormat {
         DXGI_FORMAT::DXGI_FORMAT_R8G8B8A8_SINT => DataFormat::R8G8B8A8_SINT,
         DXGI_FORMAT::DXGI_FORMAT_R8G8B8A8_TYPELESS => DataFormat::R8G8B8A8_UNORM,
         DXGI_FORMAT::DXGI_FORMAT_R8G8B8A8_UNORM_SRGB => DataFormat::R8G8B8A8_SRGB,
+        DXGI_FORMAT::DXGI_FORMAT_B8G8R8A8_UNORM => DataFormat::B8G8R8A8_UNORM,
+        DXGI_FORMAT::DXGI_FORMAT_B8G8R8A8_TYPELESS => DataFormat::B8G8R8A8_UNORM,
+        DXGI_FORMAT::DXGI_FORMAT_B8G8R8A8_UNORM_SRGB => DataFormat::B8G8R8A8_SRGB,
+        DXGI_FORMAT::DXGI_FORMAT_R10G10B10A2_UNORM => DataFormat::A2B10G10R10_UNORM_PACK32,
+        DXGI_FORMAT::DXGI_FORMAT_R10G10B10A2_TYPELESS => DataFormat::A2B10G10R10_UNORM_PACK32,
+        DXGI_FORMAT::DXGI_FORMAT_R16G16B16A16_FLOAT => DataFormat::R16G16B16A16_SFLOAT,
+        DXGI_FORMAT::DXGI_FORMAT_R16G16B16A16_TYPELESS => DataFormat::R16G16B16A16_SFLOAT,
+        DXGI_FORMAT::DXGI_FORMAT_R32G32B32A32_FLOAT => DataFormat::R32G32B32A32_SFLOAT,
+        DXGI_FORMAT::DXGI_FORMAT_R32G32B32A32_TYPELESS => DataFormat::R32G32B32A32_SFLOAT,
 
         // TODO: Figure out all the other format mappings.
         // DXGI_FORMAT::DXGI_FORMAT_B4G4R4A4_UNORM => DataFormat::R4G4B4A4_UNORM_PACK16,
         // DXGI_FORMAT::DXGI_FORMAT_B5G6R5_UNORM => DataFormat::R5G6B5_UNORM_PACK16,
         // DXGI_FORMAT::DXGI_FORMAT_R8_TYPELESS => DataFormat::R8_UNORM,
         // DXGI_FORMAT::DXGI_FORMAT_R8G8_TYPELESS => DataFormat::R8G8_UNORM,
-        // DXGI_FORMAT::DXGI_FORMAT_B8G8R8A8_TYPELESS => DataFormat::B8G8R8A8_UNORM,
-        // DXGI_FORMAT::DXGI_FORMAT_R10G10B10A2_TYPELESS => DataFormat::A2R10G10B10_UNORM_PACK32,
         // DXGI_FORMAT::DXGI_FORMAT_R16_TYPELESS => DataFormat::R16_UNORM,
         // DXGI_FORMAT::DXGI_FORMAT_R16G16_TYPELESS => DataFormat::R16G16_UNORM,
-        // DXGI_FORMAT::DXGI_FORMAT_R16G16B16A16_TYPELESS => DataFormat::R16G16B16A16_UNORM,
         // DXGI_FORMAT::DXGI_FORMAT_R32_TYPELESS => DataFormat::R32_UINT,
         // DXGI_FORMAT::DXGI_FORMAT_R32G32_TYPELESS => DataFormat::R32G32_UINT,
         // DXGI_FORMAT::DXGI_FORMAT_R32G32B32_TYPELESS => DataFormat::R32G32B32_UINT,
-        // DXGI_FORMAT::DXGI_FORMAT_R32G32B32A32_TYPELESS => DataFormat::R32G32B32A32_UINT,
         // DXGI_FORMAT::DXGI_FORMAT_R11G11B10_FLOAT => DataFormat::B10G11R11_UFLOAT_PACK32,
         // DXGI_FORMAT::DXGI_FORMAT_R9G9B9E5_SHAREDEXP => DataFormat::E5B9G9R9_UFLOAT_PACK32,
         // DXGI_FORMAT::DXGI_FORMAT_R24G8_TYPELESS => DataFormat::X8_D24_UNORM_PACK32,
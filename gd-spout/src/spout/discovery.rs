@@ -0,0 +1,50 @@
+use crate::spout::frame_store::FrameStore;
+
+// Lists the senders of every transport, native Spout senders first.
+pub fn sender_names() -> Vec<String> {
+    let mut names = native_sender_names();
+
+    for name in FrameStore::shared().sender_names() {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+
+    names
+}
+
+#[cfg(target_os = "windows")]
+fn native_sender_names() -> Vec<String> {
+    spout_sys::get_sender_names()
+}
+
+#[cfg(not(target_os = "windows"))]
+fn native_sender_names() -> Vec<String> {
+    Vec::new()
+}
+
+// The active native Spout sender, or else the most recently started CPU sender.
+pub fn active_sender() -> Option<String> {
+    native_active_sender().or_else(|| FrameStore::shared().active_sender())
+}
+
+#[cfg(target_os = "windows")]
+fn native_active_sender() -> Option<String> {
+    spout_sys::get_active_sender()
+}
+
+#[cfg(not(target_os = "windows"))]
+fn native_active_sender() -> Option<String> {
+    None
+}
+
+// The executable of the application running the sender, only known for native Spout senders.
+#[cfg(target_os = "windows")]
+pub fn sender_host_path(name: &str) -> Option<String> {
+    spout_sys::get_sender_host_path(name)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn sender_host_path(_name: &str) -> Option<String> {
+    None
+}
@@ -0,0 +1,330 @@
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+// Frames of CPU senders are exchanged as files holding a small header followed by tightly packed RGBA8 pixels. Where
+// the platform has a RAM backed directory it is used, so the files are effectively shared memory. Frames are written to
+// a temporary file first and renamed into place, so readers never observe a partially written frame.
+const MAGIC: &[u8; 4] = b"GDSF";
+const VERSION: u32 = 1;
+const HEADER_SIZE: usize = 24;
+const EXTENSION: &str = "frame";
+// Names the most recently started sender, like the active sender of Spout. It has no frame extension, so it is not
+// listed as a sender.
+const ACTIVE_SENDER_FILE: &str = "active_sender";
+// Larger than any texture Godot creates, while keeping a corrupt header from allocating gigabytes.
+const MAX_DIMENSION: u32 = 16384;
+
+pub struct FrameHeader {
+    pub width: u32,
+    pub height: u32,
+    pub frame: u64,
+}
+
+pub struct Frame {
+    pub header: FrameHeader,
+    pub data: Vec<u8>,
+}
+
+// The directory frames are exchanged in. Every process uses the shared store, tests use stores of their own.
+pub struct FrameStore {
+    directory: PathBuf,
+}
+
+static SHARED: LazyLock<FrameStore> = LazyLock::new(|| {
+    let shared_memory = PathBuf::from("/dev/shm");
+
+    let root = if shared_memory.is_dir() {
+        shared_memory
+    } else {
+        std::env::temp_dir()
+    };
+
+    FrameStore::new(root.join("gd-spout"))
+});
+
+impl FrameStore {
+    pub fn new(directory: PathBuf) -> Self {
+        Self { directory }
+    }
+
+    pub fn shared() -> &'static FrameStore {
+        &SHARED
+    }
+
+    pub fn sender_names(&self) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(&self.directory) else {
+            return Vec::new();
+        };
+
+        entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == EXTENSION))
+            .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+            .collect()
+    }
+
+    // Reads the frame of the given sender, unless it is not newer than `last_frame`.
+    pub fn read_frame(&self, name: &str, last_frame: Option<u64>) -> io::Result<Option<Frame>> {
+        let mut file = File::open(self.frame_path(name))?;
+        let mut header = [0; HEADER_SIZE];
+        file.read_exact(&mut header)?;
+
+        let header = parse_header(&header)?;
+
+        if last_frame.is_some_and(|last_frame| header.frame <= last_frame) {
+            return Ok(None);
+        }
+
+        let size = data_size(header.width, header.height)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Frame size is out of range."))?;
+
+        if file.metadata()?.len() != (HEADER_SIZE + size) as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Frame file does not match its size.",
+            ));
+        }
+
+        let mut data = vec![0; size];
+        file.read_exact(&mut data)?;
+
+        Ok(Some(Frame { header, data }))
+    }
+
+    pub fn write_frame(&self, name: &str, header: &FrameHeader, data: &[u8]) -> io::Result<()> {
+        if data_size(header.width, header.height) != Some(data.len()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Frame data does not match its size.",
+            ));
+        }
+
+        fs::create_dir_all(&self.directory)?;
+
+        let path = self.frame_path(name);
+        let temporary_path = path.with_extension("tmp");
+
+        {
+            let mut file = File::create(&temporary_path)?;
+            file.write_all(&encode_header(header))?;
+            file.write_all(data)?;
+        }
+
+        fs::rename(temporary_path, path)
+    }
+
+    pub fn remove_frame(&self, name: &str) {
+        let _ = fs::remove_file(self.frame_path(name));
+    }
+
+    pub fn set_active_sender(&self, name: &str) -> io::Result<()> {
+        fs::create_dir_all(&self.directory)?;
+
+        let path = self.directory.join(ACTIVE_SENDER_FILE);
+        let temporary_path = path.with_extension("tmp");
+        fs::write(&temporary_path, name)?;
+        fs::rename(temporary_path, path)
+    }
+
+    // The active sender, once it published a frame. Nothing is active after it stopped, until another sender starts.
+    pub fn active_sender(&self) -> Option<String> {
+        let name = fs::read_to_string(self.directory.join(ACTIVE_SENDER_FILE)).ok()?;
+        self.frame_path(&name).is_file().then_some(name)
+    }
+
+    pub fn clear_active_sender(&self, name: &str) {
+        let path = self.directory.join(ACTIVE_SENDER_FILE);
+
+        if fs::read_to_string(&path).is_ok_and(|active_sender| active_sender == name) {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    fn frame_path(&self, name: &str) -> PathBuf {
+        self.directory.join(format!("{}.{EXTENSION}", file_name(name)))
+    }
+}
+
+// Sender names are free text, so anything that can't be part of a file name is replaced.
+fn file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+fn encode_header(header: &FrameHeader) -> [u8; HEADER_SIZE] {
+    let mut bytes = [0; HEADER_SIZE];
+    bytes[0..4].copy_from_slice(MAGIC);
+    bytes[4..8].copy_from_slice(&VERSION.to_le_bytes());
+    bytes[8..12].copy_from_slice(&header.width.to_le_bytes());
+    bytes[12..16].copy_from_slice(&header.height.to_le_bytes());
+    bytes[16..24].copy_from_slice(&header.frame.to_le_bytes());
+    bytes
+}
+
+fn parse_header(header: &[u8; HEADER_SIZE]) -> io::Result<FrameHeader> {
+    let u32_at = |offset: usize| u32::from_le_bytes(header[offset..offset + 4].try_into().unwrap());
+
+    if &header[0..4] != MAGIC || u32_at(4) != VERSION {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Unsupported frame file."));
+    }
+
+    Ok(FrameHeader {
+        width: u32_at(8),
+        height: u32_at(12),
+        frame: u64::from_le_bytes(header[16..24].try_into().unwrap()),
+    })
+}
+
+fn data_size(width: u32, height: u32) -> Option<usize> {
+    if width > MAX_DIMENSION || height > MAX_DIMENSION {
+        return None;
+    }
+
+    (width as usize).checked_mul(height as usize)?.checked_mul(4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_round_trip() {
+        let header = FrameHeader {
+            width: 1920,
+            height: 1080,
+            frame: 42,
+        };
+
+        let parsed = parse_header(&encode_header(&header)).unwrap();
+        assert_eq!(parsed.width, 1920);
+        assert_eq!(parsed.height, 1080);
+        assert_eq!(parsed.frame, 42);
+    }
+
+    #[test]
+    fn header_with_other_magic_or_version_is_rejected() {
+        let header = FrameHeader {
+            width: 1,
+            height: 1,
+            frame: 0,
+        };
+
+        let mut bytes = encode_header(&header);
+        bytes[0] = b'X';
+        assert!(parse_header(&bytes).is_err());
+
+        let mut bytes = encode_header(&header);
+        bytes[4..8].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        assert!(parse_header(&bytes).is_err());
+    }
+
+    #[test]
+    fn data_size_is_bounded() {
+        assert_eq!(data_size(2, 3), Some(24));
+        assert_eq!(
+            data_size(MAX_DIMENSION, MAX_DIMENSION),
+            Some(MAX_DIMENSION as usize * MAX_DIMENSION as usize * 4)
+        );
+        assert_eq!(data_size(MAX_DIMENSION + 1, 1), None);
+        assert_eq!(data_size(1, u32::MAX), None);
+    }
+
+    // A store in a directory of its own, removed again when dropped.
+    struct TestStore {
+        store: FrameStore,
+    }
+
+    impl TestStore {
+        fn new(test: &str) -> Self {
+            let directory = std::env::temp_dir().join(format!("gd-spout-{test}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&directory);
+
+            Self {
+                store: FrameStore::new(directory),
+            }
+        }
+    }
+
+    impl Drop for TestStore {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.store.directory);
+        }
+    }
+
+    #[test]
+    fn frame_round_trip() {
+        let test_store = TestStore::new("frame-round-trip");
+        let store = &test_store.store;
+        let header = FrameHeader {
+            width: 2,
+            height: 1,
+            frame: 7,
+        };
+        let data = [1, 2, 3, 4, 5, 6, 7, 8];
+
+        store.write_frame("Sender", &header, &data).unwrap();
+        assert_eq!(store.sender_names(), vec!["Sender".to_owned()]);
+
+        let frame = store.read_frame("Sender", None).unwrap().unwrap();
+        assert_eq!((frame.header.width, frame.header.height, frame.header.frame), (2, 1, 7));
+        assert_eq!(frame.data, data);
+
+        assert!(store.read_frame("Sender", Some(7)).unwrap().is_none());
+
+        store.remove_frame("Sender");
+        assert!(store.read_frame("Sender", None).is_err());
+        assert!(store.sender_names().is_empty());
+    }
+
+    #[test]
+    fn frame_with_mismatched_data_is_not_written() {
+        let test_store = TestStore::new("mismatched-data");
+        let store = &test_store.store;
+        let header = FrameHeader {
+            width: 2,
+            height: 2,
+            frame: 0,
+        };
+
+        assert!(store.write_frame("Sender", &header, &[0; 4]).is_err());
+        assert!(store.sender_names().is_empty());
+    }
+
+    #[test]
+    fn active_sender_needs_a_frame() {
+        let test_store = TestStore::new("active-sender");
+        let store = &test_store.store;
+        let header = FrameHeader {
+            width: 1,
+            height: 1,
+            frame: 0,
+        };
+
+        store.set_active_sender("Sender").unwrap();
+        assert_eq!(store.active_sender(), None);
+
+        store.write_frame("Sender", &header, &[0; 4]).unwrap();
+        assert_eq!(store.active_sender(), Some("Sender".to_owned()));
+
+        // Only the active sender clears itself.
+        store.clear_active_sender("Other");
+        assert_eq!(store.active_sender(), Some("Sender".to_owned()));
+
+        store.clear_active_sender("Sender");
+        assert_eq!(store.active_sender(), None);
+        assert_eq!(store.sender_names(), vec!["Sender".to_owned()]);
+    }
+
+    #[test]
+    fn file_name_replaces_reserved_characters() {
+        assert_eq!(file_name("Godot Sender"), "Godot Sender");
+        assert_eq!(file_name("a/b\\c:d*e?f\"g<h>i|j"), "a_b_c_d_e_f_g_h_i_j");
+    }
+}
@@ -1,27 +1,607 @@
+use crate::diagnostics;
+use crate::diagnostics::{Diagnostics, ErrorCode};
+use crate::monitors;
+use crate::spout::receiver::convert::{FormatConverter, storage_format};
+use crate::spout::receiver::display::DisplayTexture;
+use crate::spout::receiver::ring::FrameRing;
+use crate::spout::registry;
+use crate::spout::registry::Backend;
+use crate::spout::render_thread::call_on_render_thread_for;
+use godot::classes::RenderingServer;
+use godot::classes::image::Format as ImageFormat;
+use godot::classes::rendering_device::{DataFormat, TextureUsageBits};
 use godot::prelude::*;
+use std::borrow::Cow;
 use std::error::Error;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+mod convert;
+mod cpu;
+mod display;
 #[cfg(target_os = "windows")]
 mod dx12;
 mod no_op;
+mod ring;
 
-pub trait SpoutReceiver {
-    fn rid(&self) -> Rid;
+const CONSECUTIVE_UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+
+// Backends either upload frames into the texture they are created with, replacing its contents with `texture_replace` so
+// the RID handed out to Godot never changes, or receive them into a rendering device texture of their own, which is
+// copied into it. The texture itself is owned by the `ReceiverHandle`.
+pub trait SpoutReceiver: Send {
     fn set_sender_name(&mut self, name: &str);
     fn width(&self) -> i32;
     fn height(&self) -> i32;
+    fn format(&self) -> DataFormat;
+    fn is_connected(&self) -> bool;
+    fn frame(&self) -> i64;
+    // The rendering device texture holding the received frame, owned by the backend. Invalid for backends uploading into
+    // the receiver texture themselves.
+    fn rd_texture(&self) -> Rid;
+    // Returns whether a frame was received into `rd_texture`, or, without one, whether the texture contents were replaced.
     fn update_resource(&mut self) -> bool;
+    // Called after the texture contents were replaced by someone else, the next update has to restore them.
+    fn reset_resource(&mut self);
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReceiverStatus {
+    pub sender_name: String,
+    pub connected: bool,
+    pub width: i32,
+    pub height: i32,
+    pub format: DataFormat,
+    pub frame: i64,
+    // Counts the frames copied into the receiver texture, which change its contents without replacing it.
+    pub received_frames: u64,
+}
+
+impl Default for ReceiverStatus {
+    fn default() -> Self {
+        Self {
+            sender_name: String::new(),
+            connected: false,
+            width: 1,
+            height: 1,
+            format: DataFormat::MAX,
+            frame: 0,
+            received_frames: 0,
+        }
+    }
+}
+
+pub struct Readback {
+    // The frame shown by the texture when it was read back.
+    pub frame: i64,
+    pub width: i32,
+    pub height: i32,
+    pub format: DataFormat,
+    pub data: Vec<u8>,
+}
+
+// Received frames pass through the converter when enabled, before they are copied into the receiver texture.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConversionSettings {
+    // `None` keeps the format of the sender.
+    pub target_format: Option<DataFormat>,
+    pub generate_mipmaps: bool,
+    // `0` copies frames into a single texture shown by the receiver texture, which may be sampled while the next frame
+    // is copied into it. Otherwise frames rotate through `ring_depth + 1` textures, see `FrameRing`.
+    pub ring_depth: usize,
+    // Usage of the receiver texture.
+    pub usage: TextureUsageBits,
+}
+
+impl Default for ConversionSettings {
+    fn default() -> Self {
+        Self {
+            target_format: None,
+            generate_mipmaps: false,
+            ring_depth: 1,
+            usage: TextureUsageBits::SAMPLING_BIT | TextureUsageBits::CAN_COPY_FROM_BIT,
+        }
+    }
+}
+
+impl ConversionSettings {
+    fn is_converting(&self) -> bool {
+        self.target_format.is_some() || self.generate_mipmaps
+    }
 }
 
-pub fn create_receiver(driver_name: &str) -> Box<dyn SpoutReceiver> {
-    let receiver = match driver_name {
-        #[cfg(target_os = "windows")]
-        "d3d12" => dx12::D3D12SpoutReceiver::new(),
-        _ => Ok(no_op::NoOpReceiver::new()),
+// Arranges texture data read back from the GPU for an image. BGRA8, the default format of Spout senders, has its red
+// and blue channels swapped, and 10 bit RGB is widened to floats, as images have neither layout.
+pub fn image_data(data_format: DataFormat, data: &[u8]) -> Option<(ImageFormat, Cow<'_, [u8]>)> {
+    let image_data = match data_format {
+        DataFormat::R8_UNORM => (ImageFormat::R8, Cow::Borrowed(data)),
+        DataFormat::R8G8_UNORM => (ImageFormat::RG8, Cow::Borrowed(data)),
+        DataFormat::R8G8B8A8_UNORM | DataFormat::R8G8B8A8_SRGB => (ImageFormat::RGBA8, Cow::Borrowed(data)),
+        DataFormat::B8G8R8A8_UNORM | DataFormat::B8G8R8A8_SRGB => (ImageFormat::RGBA8, Cow::Owned(swap_red_blue(data))),
+        DataFormat::A2B10G10R10_UNORM_PACK32 => (ImageFormat::RGBAF, Cow::Owned(widen_rgb10_a2(data))),
+        DataFormat::R16G16B16A16_SFLOAT => (ImageFormat::RGBAH, Cow::Borrowed(data)),
+        DataFormat::R32G32B32A32_SFLOAT => (ImageFormat::RGBAF, Cow::Borrowed(data)),
+        _ => return None,
     };
 
-    receiver.unwrap_or_else(|err: Box<dyn Error>| {
-        godot_error!("{err}; Failed to create receiver: {driver_name}; Falling back on no op implementation.");
+    Some(image_data)
+}
+
+fn swap_red_blue(data: &[u8]) -> Vec<u8> {
+    data.chunks_exact(4)
+        .flat_map(|pixel| [pixel[2], pixel[1], pixel[0], pixel[3]])
+        .collect()
+}
+
+fn widen_rgb10_a2(data: &[u8]) -> Vec<u8> {
+    data.chunks_exact(4)
+        .flat_map(|pixel| {
+            let packed = u32::from_le_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]);
+            let channel = |shift: u32, max: u32| ((packed >> shift) & max) as f32 / max as f32;
+
+            [channel(0, 1023), channel(10, 1023), channel(20, 1023), channel(30, 3)]
+        })
+        .flat_map(f32::to_le_bytes)
+        .collect()
+}
+
+type CreateReceiver = fn(Rid) -> Result<Box<dyn SpoutReceiver>, Box<dyn Error>>;
+
+const BACKENDS: &[Backend<CreateReceiver>] = &[
+    #[cfg(target_os = "windows")]
+    Backend {
+        name: "dx12",
+        drivers: &["d3d12"],
+        platforms: &["windows"],
+        priority: 100,
+        probe: crate::spout::d3d12_util::probe,
+        create: dx12::D3D12SpoutReceiver::new,
+    },
+    // Without a native backend frames still arrive from CPU senders, which also covers the Compatibility renderer.
+    Backend {
+        name: "cpu",
+        drivers: &[],
+        platforms: &[],
+        priority: 0,
+        probe: registry::always_available,
+        create: cpu::CpuSpoutReceiver::new,
+    },
+];
+
+pub fn create_receiver(driver_name: &str, texture_rid: Rid) -> Box<dyn SpoutReceiver> {
+    registry::select("receiver", BACKENDS, driver_name, |create| create(texture_rid)).unwrap_or_else(|rejections| {
+        diagnostics::error(
+            ErrorCode::BackendUnavailable,
+            format!("{rejections}; Failed to create receiver: {driver_name}; Falling back on no op implementation."),
+        );
         no_op::NoOpReceiver::new()
     })
 }
+
+// The backend is created, updated and released on the rendering thread. Settings are handed off from the owning thread
+// and the texture properties are mirrored back after every update, so they can be read from any thread.
+#[derive(Clone)]
+pub struct ReceiverHandle {
+    texture_rid: Rid,
+    state: Arc<Mutex<ReceiverState>>,
+    diagnostics: Diagnostics,
+}
+
+struct ReceiverState {
+    backend: Option<Box<dyn SpoutReceiver>>,
+    pending_name: Option<String>,
+    pending_conversion: Option<ConversionSettings>,
+    conversion: ConversionSettings,
+    ring: Option<FrameRing>,
+    converter: Option<FormatConverter>,
+    display: DisplayTexture,
+    status: ReceiverStatus,
+    updated: bool,
+    readbacks: Vec<Readback>,
+    readbacks_in_flight: usize,
+    last_update: Option<Instant>,
+}
+
+impl ReceiverState {
+    fn sync_from_backend(&mut self) {
+        if let Some(backend) = &self.backend {
+            self.status.connected = backend.is_connected();
+            self.status.width = backend.width();
+            self.status.height = backend.height();
+            self.status.format = self.conversion.target_format.unwrap_or_else(|| backend.format());
+            self.status.frame = backend.frame();
+        }
+    }
+
+    // Frames the sender produced between two consecutive updates count as dropped. Updates further apart, such as while
+    // the receiver is idle, are not consecutive.
+    fn count_frames(&mut self, previous_frame: i64) {
+        let now = Instant::now();
+        let consecutive = self
+            .last_update
+            .is_some_and(|last_update| now.duration_since(last_update) < CONSECUTIVE_UPDATE_INTERVAL);
+
+        self.last_update = Some(now);
+
+        let frame = self.status.frame;
+
+        if frame <= previous_frame {
+            return;
+        }
+
+        monitors::frame_received();
+
+        if consecutive && previous_frame > 0 {
+            monitors::frames_dropped((frame - previous_frame - 1) as u64);
+        }
+    }
+}
+
+impl ReceiverHandle {
+    pub fn new() -> Self {
+        let texture_rid = RenderingServer::singleton().texture_2d_placeholder_create();
+        let state = Arc::new(Mutex::new(ReceiverState {
+            backend: None,
+            pending_name: None,
+            pending_conversion: None,
+            conversion: ConversionSettings::default(),
+            ring: None,
+            converter: None,
+            display: DisplayTexture::default(),
+            status: ReceiverStatus::default(),
+            updated: false,
+            readbacks: Vec::new(),
+            readbacks_in_flight: 0,
+            last_update: None,
+        }));
+
+        let diagnostics = Diagnostics::default();
+        let task_state = state.clone();
+        call_on_render_thread_for(&diagnostics, move || {
+            let driver_name = RenderingServer::singleton()
+                .get_current_rendering_driver_name()
+                .to_string();
+
+            let backend = create_receiver(&driver_name, texture_rid);
+
+            if let Ok(mut state) = task_state.lock() {
+                state.backend = Some(backend);
+                state.sync_from_backend();
+                state.updated = true;
+            }
+        });
+
+        Self {
+            texture_rid,
+            state,
+            diagnostics,
+        }
+    }
+
+    pub fn rid(&self) -> Rid {
+        self.texture_rid
+    }
+
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    pub fn status(&self) -> ReceiverStatus {
+        self.state.lock().map(|state| state.status.clone()).unwrap_or_default()
+    }
+
+    pub fn set_sender_name(&self, name: &str) {
+        if let Ok(mut state) = self.state.lock() {
+            state.pending_name = Some(name.to_owned());
+        }
+    }
+
+    pub fn set_conversion(&self, conversion: ConversionSettings) {
+        if let Ok(mut state) = self.state.lock() {
+            state.pending_conversion = Some(conversion);
+        }
+    }
+
+    pub fn update_resource(&self) {
+        let texture_rid = self.texture_rid;
+        let state = self.state.clone();
+
+        call_on_render_thread_for(&self.diagnostics, move || {
+            let Ok(mut state) = state.lock() else {
+                return;
+            };
+
+            let ReceiverState {
+                backend: Some(backend),
+                pending_name,
+                pending_conversion,
+                conversion,
+                ring,
+                converter,
+                display,
+                status,
+                ..
+            } = &mut *state
+            else {
+                return;
+            };
+
+            if let Some(name) = pending_name.take() {
+                backend.set_sender_name(&name);
+                status.sender_name = name;
+                // Frame counters of different senders are unrelated.
+                status.frame = 0;
+            }
+
+            if let Some(pending_conversion) = pending_conversion.take() {
+                *conversion = pending_conversion;
+            }
+
+            let (received, updated) = monitors::time_receive(|| {
+                let received = backend.update_resource();
+                let source_rid = backend.rd_texture();
+
+                if !source_rid.is_valid() {
+                    return (received, received);
+                }
+
+                let updated = received
+                    && present_frame(
+                        source_rid,
+                        backend.format(),
+                        *conversion,
+                        ring,
+                        converter,
+                        display,
+                        texture_rid,
+                    );
+
+                (received, updated)
+            });
+
+            let previous_frame = state.status.frame;
+
+            // Connection state and frame counters change even when no new resource was created.
+            state.sync_from_backend();
+            state.count_frames(previous_frame);
+            state.status.received_frames += received as u64;
+            state.updated |= updated;
+        });
+    }
+
+    // Replaces the contents of the receiver texture with the given texture, which is consumed. The backend restores its
+    // own contents on the next received frame.
+    pub fn replace_texture(&self, texture_rid: Rid) {
+        let target_rid = self.texture_rid;
+        let state = self.state.clone();
+
+        call_on_render_thread_for(&self.diagnostics, move || {
+            RenderingServer::singleton().texture_replace(target_rid, texture_rid);
+
+            let Ok(mut state) = state.lock() else {
+                return;
+            };
+
+            state.display.reset();
+
+            if let Some(backend) = &mut state.backend {
+                backend.reset_resource();
+            }
+        });
+    }
+
+    // Reads the texture back asynchronously, completed readbacks are collected with `take_readbacks`. Returns false when
+    // there is no rendering device to read back from.
+    pub fn request_readback(&self) -> bool {
+        if RenderingServer::singleton().get_rendering_device().is_none() {
+            return false;
+        }
+
+        let texture_rid = self.texture_rid;
+        let state = self.state.clone();
+
+        if let Ok(mut state) = state.lock() {
+            state.readbacks_in_flight += 1;
+        }
+
+        call_on_render_thread_for(&self.diagnostics, move || {
+            let mut rendering_server = RenderingServer::singleton();
+            let Some(mut rendering_device) = rendering_server.get_rendering_device() else {
+                finish_readback(&state, None);
+                return;
+            };
+
+            let rd_texture_rid = rendering_server.texture_get_rd_texture(texture_rid);
+            let Some(texture_format) = rendering_device.texture_get_format(rd_texture_rid) else {
+                diagnostics::error(
+                    ErrorCode::InvalidTexture,
+                    "Unable to obtain the receiver texture format.",
+                );
+                finish_readback(&state, None);
+                return;
+            };
+
+            let frame = state.lock().map(|state| state.status.frame).unwrap_or_default();
+            let width = texture_format.get_width() as i32;
+            let height = texture_format.get_height() as i32;
+            let format = texture_format.get_format();
+
+            let callback = Callable::from_sync_fn("gd_spout_readback", move |args| {
+                let data = args
+                    .first()
+                    .and_then(|data| data.try_to::<PackedByteArray>().ok())
+                    .map(|data| data.to_vec())
+                    .unwrap_or_default();
+
+                let readback = Readback {
+                    frame,
+                    width,
+                    height,
+                    format,
+                    data,
+                };
+
+                finish_readback(&state, Some(readback));
+                Ok(Variant::nil())
+            });
+
+            if rendering_device.texture_get_data_async(rd_texture_rid, 0, &callback) != godot::global::Error::OK {
+                diagnostics::error(ErrorCode::InvalidTexture, "Failed to read back the receiver texture.");
+                finish_readback(&state, None);
+            }
+        });
+
+        true
+    }
+
+    pub fn is_readback_pending(&self) -> bool {
+        self.state.lock().is_ok_and(|state| state.readbacks_in_flight > 0)
+    }
+
+    pub fn take_readbacks(&self) -> Vec<Readback> {
+        self.state
+            .lock()
+            .map(|mut state| std::mem::take(&mut state.readbacks))
+            .unwrap_or_default()
+    }
+
+    // Returns whether the backend produced a new resource since the last call.
+    pub fn take_updated(&self) -> bool {
+        self.state
+            .lock()
+            .map(|mut state| std::mem::take(&mut state.updated))
+            .unwrap_or(false)
+    }
+
+    pub fn release(&self) {
+        let texture_rid = self.texture_rid;
+        let state = self.state.clone();
+
+        call_on_render_thread_for(&self.diagnostics, move || {
+            if let Ok(mut state) = state.lock() {
+                state.backend = None;
+                state.ring = None;
+                state.converter = None;
+            }
+
+            RenderingServer::singleton().free_rid(texture_rid);
+        });
+    }
+}
+
+fn finish_readback(state: &Mutex<ReceiverState>, readback: Option<Readback>) {
+    if let Ok(mut state) = state.lock() {
+        state.readbacks_in_flight = state.readbacks_in_flight.saturating_sub(1);
+        state.readbacks.extend(readback);
+    }
+}
+
+// Shows a received frame through the receiver texture, converting it first when enabled. Frames are then copied into
+// the shown slot of the ring buffer, or into the display texture when there is no ring.
+// Returns whether the receiver texture now shows a newly attached texture.
+fn present_frame(
+    source_rid: Rid,
+    source_format: DataFormat,
+    conversion: ConversionSettings,
+    ring: &mut Option<FrameRing>,
+    converter: &mut Option<FormatConverter>,
+    display: &mut DisplayTexture,
+    texture_rid: Rid,
+) -> bool {
+    let source_rid = if conversion.is_converting() {
+        let target_format = conversion
+            .target_format
+            .unwrap_or_else(|| storage_format(source_format));
+
+        if converter.as_ref().is_none_or(|converter| {
+            converter.target_format() != target_format || converter.generate_mipmaps() != conversion.generate_mipmaps
+        }) {
+            *converter = FormatConverter::new(target_format, conversion.generate_mipmaps);
+        }
+
+        let Some(target_rid) = converter
+            .as_mut()
+            .and_then(|converter| converter.convert(source_rid, source_format))
+        else {
+            return false;
+        };
+
+        target_rid
+    } else {
+        *converter = None;
+        source_rid
+    };
+
+    if conversion.ring_depth == 0 {
+        // The display texture is attached again now that the ring no longer replaces it.
+        if ring.take().is_some() {
+            display.reset();
+        }
+
+        return display.present(texture_rid, source_rid, conversion.usage);
+    }
+
+    if ring.as_ref().is_none_or(|ring| ring.depth() != conversion.ring_depth) {
+        *ring = Some(FrameRing::new(conversion.ring_depth));
+    }
+
+    // Showing a ring slot replaced and freed the display texture.
+    display.reset();
+
+    ring.as_mut()
+        .is_some_and(|ring| ring.present(texture_rid, source_rid, conversion.usage))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn floats(data: &[u8]) -> Vec<f32> {
+        data.chunks_exact(4)
+            .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn image_layouts_are_kept() {
+        let data = [1, 2, 3, 4];
+
+        for (data_format, image_format) in [
+            (DataFormat::R8G8B8A8_UNORM, ImageFormat::RGBA8),
+            (DataFormat::R8G8B8A8_SRGB, ImageFormat::RGBA8),
+            (DataFormat::R8G8_UNORM, ImageFormat::RG8),
+            (DataFormat::R8_UNORM, ImageFormat::R8),
+        ] {
+            let (format, converted) = image_data(data_format, &data).unwrap();
+            assert_eq!(format, image_format);
+            assert!(matches!(converted, Cow::Borrowed(_)));
+            assert_eq!(converted.as_ref(), data);
+        }
+    }
+
+    #[test]
+    fn bgra8_is_swizzled_to_rgba8() {
+        let data = [1, 2, 3, 4, 5, 6, 7, 8];
+
+        for data_format in [DataFormat::B8G8R8A8_UNORM, DataFormat::B8G8R8A8_SRGB] {
+            let (format, converted) = image_data(data_format, &data).unwrap();
+            assert_eq!(format, ImageFormat::RGBA8);
+            assert_eq!(converted.as_ref(), [3, 2, 1, 4, 7, 6, 5, 8]);
+        }
+    }
+
+    #[test]
+    fn rgb10_a2_is_widened_to_floats() {
+        let packed: u32 = 1023 | (512 << 10) | (341 << 20) | (3 << 30);
+        let (format, converted) = image_data(DataFormat::A2B10G10R10_UNORM_PACK32, &packed.to_le_bytes()).unwrap();
+
+        assert_eq!(format, ImageFormat::RGBAF);
+        assert_eq!(floats(&converted), [1.0, 512.0 / 1023.0, 341.0 / 1023.0, 1.0]);
+    }
+
+    #[test]
+    fn other_formats_are_unsupported() {
+        assert!(image_data(DataFormat::R16_UNORM, &[0; 2]).is_none());
+        assert!(image_data(DataFormat::D32_SFLOAT, &[0; 4]).is_none());
+    }
+}
@@ -0,0 +1,403 @@
+use crate::diagnostics;
+use crate::diagnostics::ErrorCode;
+use crate::spout::compute::{compile_shader, dispatch};
+use godot::classes::rendering_device::{DataFormat, TextureUsageBits, UniformType};
+use godot::classes::{RdSamplerState, RdTextureFormat, RdTextureView, RdUniform, RenderingDevice, RenderingServer};
+use godot::prelude::*;
+
+const CONVERT_SHADER_SOURCE: &str = r#"
+#version 450
+
+layout(local_size_x = 8, local_size_y = 8, local_size_z = 1) in;
+
+layout(set = 0, binding = 0) uniform sampler2D source_texture;
+layout(set = 0, binding = 1, IMAGE_FORMAT) uniform restrict writeonly image2D target_image;
+
+layout(push_constant, std430) uniform Params {
+    ivec2 size;
+    int decode_srgb;
+    int encode_srgb;
+} params;
+
+SRGB_FUNCTIONS
+
+void main() {
+    ivec2 coord = ivec2(gl_GlobalInvocationID.xy);
+
+    if (any(greaterThanEqual(coord, params.size))) {
+        return;
+    }
+
+    // Sampling already swizzles BGRA sources and decodes sRGB formats.
+    vec4 color = texelFetch(source_texture, coord, 0);
+
+    if (params.decode_srgb != 0) {
+        color.rgb = srgb_to_linear(color.rgb);
+    }
+
+    if (params.encode_srgb != 0) {
+        color.rgb = linear_to_srgb(clamp(color.rgb, 0.0, 1.0));
+    }
+
+    imageStore(target_image, coord, color);
+}
+"#;
+
+const DOWNSAMPLE_SHADER_SOURCE: &str = r#"
+#version 450
+
+layout(local_size_x = 8, local_size_y = 8, local_size_z = 1) in;
+
+layout(set = 0, binding = 0) uniform sampler2D source_level;
+layout(set = 0, binding = 1, IMAGE_FORMAT) uniform restrict writeonly image2D target_level;
+
+layout(push_constant, std430) uniform Params {
+    ivec2 size;
+    ivec2 source_size;
+    int srgb_encoded;
+    int pad0;
+    int pad1;
+    int pad2;
+} params;
+
+SRGB_FUNCTIONS
+
+vec4 fetch(ivec2 coord) {
+    vec4 color = texelFetch(source_level, min(coord, params.source_size - 1), 0);
+
+    if (params.srgb_encoded != 0) {
+        color.rgb = srgb_to_linear(color.rgb);
+    }
+
+    return color;
+}
+
+void main() {
+    ivec2 coord = ivec2(gl_GlobalInvocationID.xy);
+
+    if (any(greaterThanEqual(coord, params.size))) {
+        return;
+    }
+
+    ivec2 source_coord = coord * 2;
+    vec4 color = (fetch(source_coord) + fetch(source_coord + ivec2(1, 0)) + fetch(source_coord + ivec2(0, 1))
+            + fetch(source_coord + ivec2(1, 1))) * 0.25;
+
+    if (params.srgb_encoded != 0) {
+        color.rgb = linear_to_srgb(color.rgb);
+    }
+
+    imageStore(target_level, coord, color);
+}
+"#;
+
+// Copies received textures into a texture of a fixed format owned by the converter with a compute pass, optionally
+// building its mipmap chain. `R8G8B8A8_SRGB` targets are stored as sRGB encoded `R8G8B8A8_UNORM`, the way Godot stores
+// color textures, as sRGB formats can't be written from a compute shader.
+pub struct FormatConverter {
+    target_format: DataFormat,
+    generate_mipmaps: bool,
+    convert_shader_rid: Rid,
+    convert_pipeline_rid: Rid,
+    downsample_shader_rid: Rid,
+    downsample_pipeline_rid: Rid,
+    sampler_rid: Rid,
+    target_rid: Rid,
+    target_size: Vector2i,
+    // Single level views of the target, the first one is written by the conversion.
+    level_rids: Vec<Rid>,
+    downsample_uniform_set_rids: Vec<Rid>,
+    uniform_set_rid: Rid,
+    uniform_set_source_rid: Rid,
+}
+
+impl Drop for FormatConverter {
+    fn drop(&mut self) {
+        let Some(mut rendering_device) = RenderingServer::singleton().get_rendering_device() else {
+            return;
+        };
+
+        // Level views and uniform sets depending on the target texture are freed along with it, as are the pipelines
+        // with their shaders.
+        for rid in [
+            self.target_rid,
+            self.sampler_rid,
+            self.convert_shader_rid,
+            self.downsample_shader_rid,
+        ] {
+            if rid.is_valid() {
+                rendering_device.free_rid(rid);
+            }
+        }
+    }
+}
+
+impl FormatConverter {
+    pub fn new(target_format: DataFormat, generate_mipmaps: bool) -> Option<Self> {
+        let Some(mut rendering_device) = RenderingServer::singleton().get_rendering_device() else {
+            diagnostics::error(ErrorCode::RenderingDeviceUnavailable, "Rendering device was null.");
+            return None;
+        };
+
+        let image_format = match target_format {
+            DataFormat::R8G8B8A8_SRGB | DataFormat::R8G8B8A8_UNORM => "rgba8",
+            DataFormat::R16G16B16A16_SFLOAT => "rgba16f",
+            DataFormat::R32G32B32A32_SFLOAT => "rgba32f",
+            format => {
+                diagnostics::error(
+                    ErrorCode::UnsupportedFormat,
+                    format!("Unsupported target format: {format:?}"),
+                );
+                return None;
+            }
+        };
+
+        let convert_shader_rid = compile_shader(
+            &mut rendering_device,
+            CONVERT_SHADER_SOURCE,
+            image_format,
+            "format conversion",
+        )?;
+
+        let downsample_shader_rid = if generate_mipmaps {
+            let Some(shader_rid) =
+                compile_shader(&mut rendering_device, DOWNSAMPLE_SHADER_SOURCE, image_format, "mipmap")
+            else {
+                rendering_device.free_rid(convert_shader_rid);
+                return None;
+            };
+
+            shader_rid
+        } else {
+            Rid::Invalid
+        };
+
+        let downsample_pipeline_rid = if downsample_shader_rid.is_valid() {
+            rendering_device.compute_pipeline_create(downsample_shader_rid)
+        } else {
+            Rid::Invalid
+        };
+
+        Some(Self {
+            target_format,
+            generate_mipmaps,
+            convert_shader_rid,
+            convert_pipeline_rid: rendering_device.compute_pipeline_create(convert_shader_rid),
+            downsample_shader_rid,
+            downsample_pipeline_rid,
+            sampler_rid: rendering_device.sampler_create(&RdSamplerState::new_gd()),
+            target_rid: Rid::Invalid,
+            target_size: Vector2i::ZERO,
+            level_rids: Vec::new(),
+            downsample_uniform_set_rids: Vec::new(),
+            uniform_set_rid: Rid::Invalid,
+            uniform_set_source_rid: Rid::Invalid,
+        })
+    }
+
+    pub fn target_format(&self) -> DataFormat {
+        self.target_format
+    }
+
+    pub fn generate_mipmaps(&self) -> bool {
+        self.generate_mipmaps
+    }
+
+    // Converts the source into the target texture and returns it. The target stays owned by the converter, so it has
+    // to be copied to be shown.
+    pub fn convert(&mut self, source_rid: Rid, source_format: DataFormat) -> Option<Rid> {
+        let Some(mut rendering_device) = RenderingServer::singleton().get_rendering_device() else {
+            diagnostics::error(ErrorCode::RenderingDeviceUnavailable, "Rendering device was null.");
+            return None;
+        };
+
+        let Some(source) = rendering_device.texture_get_format(source_rid) else {
+            diagnostics::error(
+                ErrorCode::InvalidTexture,
+                "Unable to obtain the received texture format.",
+            );
+            return None;
+        };
+
+        let size = Vector2i::new(source.get_width() as i32, source.get_height() as i32);
+        self.update_target_texture(&mut rendering_device, size);
+
+        if !self.target_rid.is_valid() || !self.update_uniform_set(&mut rendering_device, source_rid) {
+            return None;
+        }
+
+        let source_encoded = is_srgb_encoded(source_format);
+        let target_encoded = self.is_target_encoded();
+
+        let compute_list = rendering_device.compute_list_begin();
+        rendering_device.compute_list_bind_compute_pipeline(compute_list, self.convert_pipeline_rid);
+        rendering_device.compute_list_bind_uniform_set(compute_list, self.uniform_set_rid, 0);
+        dispatch(
+            &mut rendering_device,
+            compute_list,
+            size,
+            &[
+                size.x,
+                size.y,
+                (source_encoded && !target_encoded) as i32,
+                (!source_encoded && target_encoded) as i32,
+            ],
+        );
+
+        if self.generate_mipmaps {
+            self.downsample(&mut rendering_device, compute_list);
+        }
+
+        rendering_device.compute_list_end();
+
+        Some(self.target_rid)
+    }
+
+    fn is_target_encoded(&self) -> bool {
+        self.target_format == DataFormat::R8G8B8A8_SRGB
+    }
+
+    fn downsample(&self, rendering_device: &mut Gd<RenderingDevice>, compute_list: i64) {
+        rendering_device.compute_list_bind_compute_pipeline(compute_list, self.downsample_pipeline_rid);
+
+        let mut source_size = self.target_size;
+
+        for uniform_set_rid in &self.downsample_uniform_set_rids {
+            let size = (source_size / 2).coord_max(Vector2i::ONE);
+
+            // Every level reads the one written before it.
+            rendering_device.compute_list_add_barrier(compute_list);
+            rendering_device.compute_list_bind_uniform_set(compute_list, *uniform_set_rid, 0);
+            dispatch(
+                rendering_device,
+                compute_list,
+                size,
+                &[
+                    size.x,
+                    size.y,
+                    source_size.x,
+                    source_size.y,
+                    self.is_target_encoded() as i32,
+                    0,
+                    0,
+                    0,
+                ],
+            );
+
+            source_size = size;
+        }
+    }
+
+    fn update_target_texture(&mut self, rendering_device: &mut Gd<RenderingDevice>, size: Vector2i) {
+        if self.target_rid.is_valid() && self.target_size == size {
+            return;
+        }
+
+        let mipmaps = if self.generate_mipmaps {
+            size.x.max(size.y).max(1).ilog2() + 1
+        } else {
+            1
+        };
+
+        let mut format = RdTextureFormat::new_gd();
+        format.set_width(size.x as u32);
+        format.set_height(size.y as u32);
+        format.set_mipmaps(mipmaps);
+        format.set_usage_bits(
+            TextureUsageBits::SAMPLING_BIT | TextureUsageBits::STORAGE_BIT | TextureUsageBits::CAN_COPY_FROM_BIT,
+        );
+
+        if self.is_target_encoded() {
+            format.set_format(DataFormat::R8G8B8A8_UNORM);
+            format.add_shareable_format(DataFormat::R8G8B8A8_UNORM);
+            format.add_shareable_format(DataFormat::R8G8B8A8_SRGB);
+        } else {
+            format.set_format(self.target_format);
+        }
+
+        // Level views and uniform sets of the previous target are freed along with it.
+        if self.target_rid.is_valid() {
+            rendering_device.free_rid(self.target_rid);
+        }
+
+        self.target_rid = rendering_device.texture_create(&format, &RdTextureView::new_gd());
+
+        self.target_size = size;
+        self.uniform_set_rid = Rid::Invalid;
+        self.level_rids = (0..mipmaps)
+            .map(|mipmap| {
+                rendering_device.texture_create_shared_from_slice(&RdTextureView::new_gd(), self.target_rid, 0, mipmap)
+            })
+            .collect();
+
+        self.downsample_uniform_set_rids = self
+            .level_rids
+            .windows(2)
+            .map(|levels| {
+                let uniforms = [self.sampler_uniform(levels[0]), image_uniform(levels[1])];
+
+                rendering_device.uniform_set_create(&uniforms.into_iter().collect(), self.downsample_shader_rid, 0)
+            })
+            .collect();
+    }
+
+    fn update_uniform_set(&mut self, rendering_device: &mut Gd<RenderingDevice>, source_rid: Rid) -> bool {
+        // Uniform sets are invalidated when the backend frees the texture they sample.
+        if self.uniform_set_source_rid == source_rid && rendering_device.uniform_set_is_valid(self.uniform_set_rid) {
+            return true;
+        }
+
+        let Some(&target_level_rid) = self.level_rids.first() else {
+            return false;
+        };
+
+        let uniforms = [self.sampler_uniform(source_rid), image_uniform(target_level_rid)];
+
+        self.uniform_set_rid =
+            rendering_device.uniform_set_create(&uniforms.into_iter().collect(), self.convert_shader_rid, 0);
+        self.uniform_set_source_rid = source_rid;
+
+        if !self.uniform_set_rid.is_valid() {
+            diagnostics::error(
+                ErrorCode::ConversionFailed,
+                "Failed to create format conversion uniform set.",
+            );
+            return false;
+        }
+
+        true
+    }
+
+    fn sampler_uniform(&self, texture_rid: Rid) -> Gd<RdUniform> {
+        let mut uniform = RdUniform::new_gd();
+        uniform.set_uniform_type(UniformType::SAMPLER_WITH_TEXTURE);
+        uniform.set_binding(0);
+        uniform.add_id(self.sampler_rid);
+        uniform.add_id(texture_rid);
+        uniform
+    }
+}
+
+// Received frames are kept in a format that doesn't change their colors, when only mipmaps have to be generated.
+pub fn storage_format(source_format: DataFormat) -> DataFormat {
+    match source_format {
+        DataFormat::R16G16B16A16_SFLOAT | DataFormat::R32G32B32A32_SFLOAT => source_format,
+        _ => DataFormat::R8G8B8A8_SRGB,
+    }
+}
+
+fn image_uniform(texture_rid: Rid) -> Gd<RdUniform> {
+    let mut uniform = RdUniform::new_gd();
+    uniform.set_uniform_type(UniformType::IMAGE);
+    uniform.set_binding(1);
+    uniform.add_id(texture_rid);
+    uniform
+}
+
+// Spout senders conventionally share gamma encoded data in plain 8 and 10 bit formats, float formats are linear.
+fn is_srgb_encoded(format: DataFormat) -> bool {
+    matches!(
+        format,
+        DataFormat::R8G8B8A8_UNORM | DataFormat::B8G8R8A8_UNORM | DataFormat::A2B10G10R10_UNORM_PACK32
+    )
+}
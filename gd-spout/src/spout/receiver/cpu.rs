@@ -0,0 +1,123 @@
+use crate::diagnostics;
+use crate::diagnostics::ErrorCode;
+use crate::monitors;
+use crate::spout::frame_store::FrameStore;
+use crate::spout::receiver::SpoutReceiver;
+use godot::classes::image::Format as ImageFormat;
+use godot::classes::rendering_device::DataFormat;
+use godot::classes::{Image, RenderingServer};
+use godot::prelude::*;
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+// Senders that stop updating their frame for this long are considered gone, as crashed senders leave their file behind.
+const STALE_TIMEOUT: Duration = Duration::from_secs(1);
+
+// Receives frames from the frame store into an image and uploads them with the RenderingServer, so it works without a
+// RenderingDevice.
+pub struct CpuSpoutReceiver {
+    texture_rid: Rid,
+    name: String,
+    width: i32,
+    height: i32,
+    frame: Option<u64>,
+    last_frame_received: Option<Instant>,
+    needs_texture: bool,
+}
+
+impl CpuSpoutReceiver {
+    pub fn new(texture_rid: Rid) -> Result<Box<dyn SpoutReceiver>, Box<dyn Error>> {
+        Ok(Box::new(Self {
+            texture_rid,
+            name: String::new(),
+            width: 1,
+            height: 1,
+            frame: None,
+            last_frame_received: None,
+            needs_texture: true,
+        }))
+    }
+}
+
+impl SpoutReceiver for CpuSpoutReceiver {
+    fn set_sender_name(&mut self, name: &str) {
+        self.name = name.to_owned();
+        self.frame = None;
+        self.last_frame_received = None;
+    }
+
+    fn width(&self) -> i32 {
+        self.width
+    }
+
+    fn height(&self) -> i32 {
+        self.height
+    }
+
+    fn format(&self) -> DataFormat {
+        DataFormat::R8G8B8A8_UNORM
+    }
+
+    fn is_connected(&self) -> bool {
+        self.last_frame_received
+            .is_some_and(|received| received.elapsed() < STALE_TIMEOUT)
+    }
+
+    fn frame(&self) -> i64 {
+        self.frame.unwrap_or_default() as i64
+    }
+
+    fn rd_texture(&self) -> Rid {
+        // Frames are uploaded into the receiver texture itself, so there is no separate texture to copy from.
+        Rid::Invalid
+    }
+
+    fn update_resource(&mut self) -> bool {
+        if self.name.is_empty() {
+            return false;
+        }
+
+        // A missing or unreadable frame is treated like a sender that didn't produce a new frame.
+        let Ok(Some(frame)) = FrameStore::shared().read_frame(&self.name, self.frame) else {
+            return false;
+        };
+
+        let width = frame.header.width as i32;
+        let height = frame.header.height as i32;
+        let data = PackedByteArray::from(frame.data.as_slice());
+        monitors::bytes_copied(data.len() as u64);
+
+        let Some(image) = Image::create_from_data(width, height, false, ImageFormat::RGBA8, &data) else {
+            diagnostics::error(
+                ErrorCode::InvalidFrame,
+                format!("Invalid frame received from: {}", self.name),
+            );
+            return false;
+        };
+
+        self.frame = Some(frame.header.frame);
+        self.last_frame_received = Some(Instant::now());
+
+        let mut rendering_server = RenderingServer::singleton();
+
+        // Updating requires a texture of the same size, anything else gets a new texture.
+        if self.needs_texture || self.width != width || self.height != height {
+            let texture_rid = rendering_server.texture_2d_create(&image);
+            rendering_server.texture_replace(self.texture_rid, texture_rid);
+
+            self.width = width;
+            self.height = height;
+            self.needs_texture = false;
+            return true;
+        }
+
+        rendering_server.texture_2d_update(self.texture_rid, &image, 0);
+        false
+    }
+
+    fn reset_resource(&mut self) {
+        self.needs_texture = true;
+        // The current frame has to be read again to restore the texture.
+        self.frame = None;
+    }
+}
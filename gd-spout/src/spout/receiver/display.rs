@@ -0,0 +1,106 @@
+use crate::diagnostics;
+use crate::diagnostics::ErrorCode;
+use godot::classes::rendering_device::{DataFormat, TextureUsageBits};
+use godot::classes::{RdTextureView, RenderingServer};
+use godot::prelude::*;
+
+// The rendering device texture shown through the receiver texture. It is handed to the RenderingServer, which owns it
+// from then on and frees it when the contents of the receiver texture are replaced again. Frames are copied into it,
+// so the textures they come from stay owned by whoever created them.
+pub struct DisplayTexture {
+    rd_texture_rid: Rid,
+    size: Vector2i,
+    format: DataFormat,
+    mipmaps: u32,
+    usage: TextureUsageBits,
+}
+
+impl Default for DisplayTexture {
+    fn default() -> Self {
+        Self {
+            rd_texture_rid: Rid::Invalid,
+            size: Vector2i::ZERO,
+            format: DataFormat::MAX,
+            mipmaps: 0,
+            usage: TextureUsageBits::SAMPLING_BIT,
+        }
+    }
+}
+
+impl DisplayTexture {
+    // Called after the contents of the receiver texture were replaced by someone else, which freed the display texture.
+    pub fn reset(&mut self) {
+        self.rd_texture_rid = Rid::Invalid;
+    }
+
+    // Copies the source into the display texture, which is recreated and attached to `texture_rid` when the source
+    // changed its size or format. Returns whether that happened.
+    pub fn present(&mut self, texture_rid: Rid, source_rid: Rid, usage: TextureUsageBits) -> bool {
+        let mut rendering_server = RenderingServer::singleton();
+        let Some(mut rendering_device) = rendering_server.get_rendering_device() else {
+            diagnostics::error(ErrorCode::RenderingDeviceUnavailable, "Rendering device was null.");
+            return false;
+        };
+
+        let Some(mut format) = rendering_device.texture_get_format(source_rid) else {
+            diagnostics::error(
+                ErrorCode::InvalidTexture,
+                "Unable to obtain the received texture format.",
+            );
+            return false;
+        };
+
+        let size = Vector2i::new(format.get_width() as i32, format.get_height() as i32);
+        let mipmaps = format.get_mipmaps().max(1);
+        let usage = usage
+            | TextureUsageBits::SAMPLING_BIT
+            | TextureUsageBits::CAN_COPY_TO_BIT
+            | TextureUsageBits::CAN_COPY_FROM_BIT;
+
+        let recreate = !self.rd_texture_rid.is_valid()
+            || self.size != size
+            || self.format != format.get_format()
+            || self.mipmaps != mipmaps
+            || self.usage != usage;
+
+        if recreate {
+            format.set_usage_bits(usage);
+            self.rd_texture_rid = rendering_device.texture_create(&format, &RdTextureView::new_gd());
+
+            if !self.rd_texture_rid.is_valid() {
+                diagnostics::error(ErrorCode::InvalidTexture, "Failed to create the display texture.");
+                return false;
+            }
+
+            self.size = size;
+            self.format = format.get_format();
+            self.mipmaps = mipmaps;
+            self.usage = usage;
+        }
+
+        for mipmap in 0..mipmaps {
+            let level_size = Vector2i::new((size.x >> mipmap).max(1), (size.y >> mipmap).max(1));
+
+            rendering_device.texture_copy(
+                source_rid,
+                self.rd_texture_rid,
+                Vector3::ZERO,
+                Vector3::ZERO,
+                Vector3::new(level_size.x as f32, level_size.y as f32, 1.0),
+                mipmap,
+                mipmap,
+                0,
+                0,
+            );
+        }
+
+        if !recreate {
+            return false;
+        }
+
+        // Replacing frees the previous display texture along with the RenderingServer texture wrapping it.
+        let rs_texture_rid = rendering_server.texture_rd_create(self.rd_texture_rid);
+        rendering_server.texture_replace(texture_rid, rs_texture_rid);
+        true
+    }
+}
@@ -1,49 +1,53 @@
+use crate::diagnostics;
+use crate::diagnostics::ErrorCode;
 use crate::spout::d3d12_util::{convert_dxgi_to_rd_data_format, get_d3d12_device};
 use crate::spout::receiver::SpoutReceiver;
 use godot::classes::RenderingServer;
-use godot::classes::rendering_device::{TextureSamples, TextureType, TextureUsageBits};
+use godot::classes::rendering_device::{DataFormat, TextureSamples, TextureType, TextureUsageBits};
 use godot::prelude::*;
 use spout_sys::{ID3D12Resource, SpoutDX12};
 use std::ptr::NonNull;
 
+// Spout copies every frame into a D3D12 resource owned by the receiver, which is wrapped in a rendering device texture
+// the `ReceiverHandle` copies into the receiver texture. Both are recreated when the sender changes size or format.
 pub struct D3D12SpoutReceiver {
     spout: SpoutDX12,
     rd_texture_rid: Rid,
-    rs_texture_rid: Rid,
+    data_format: DataFormat,
     texture_resource: Option<NonNull<ID3D12Resource>>,
 }
 
+// SAFETY: The backend is only ever accessed from the rendering thread through a `ReceiverHandle`.
+unsafe impl Send for D3D12SpoutReceiver {}
+
 impl Drop for D3D12SpoutReceiver {
     fn drop(&mut self) {
         self.spout.release_receiver();
-        self.free_godot_resources();
+        self.free_rd_texture();
     }
 }
 
 impl D3D12SpoutReceiver {
-    pub fn new() -> Result<Box<dyn SpoutReceiver>, Box<dyn std::error::Error>> {
+    pub fn new(_texture_rid: Rid) -> Result<Box<dyn SpoutReceiver>, Box<dyn std::error::Error>> {
         let Some(device) = get_d3d12_device() else {
             return Err("Unable to obtain D3D12 Device".into());
         };
 
         let spout = SpoutDX12::new(device);
-        let rs_texture_rid = RenderingServer::singleton().texture_2d_placeholder_create();
 
         Ok(Box::new(Self {
             spout,
-            rs_texture_rid,
             rd_texture_rid: Rid::Invalid,
+            data_format: DataFormat::MAX,
             texture_resource: None,
         }))
     }
 }
 
 impl SpoutReceiver for D3D12SpoutReceiver {
-    fn rid(&self) -> Rid {
-        self.rs_texture_rid
-    }
-
     fn set_sender_name(&mut self, name: &str) {
+        // Spout keeps an existing connection when the name changes, so it has to be released to switch senders.
+        self.spout.release_receiver();
         self.spout.set_receiver_name(name);
     }
 
@@ -55,78 +59,99 @@ impl SpoutReceiver for D3D12SpoutReceiver {
         self.spout.get_sender_height() as i32
     }
 
+    fn format(&self) -> DataFormat {
+        self.data_format
+    }
+
+    fn is_connected(&self) -> bool {
+        self.spout.is_connected()
+    }
+
+    fn frame(&self) -> i64 {
+        self.spout.get_sender_frame()
+    }
+
+    fn rd_texture(&self) -> Rid {
+        self.rd_texture_rid
+    }
+
     fn update_resource(&mut self) -> bool {
-        let Some(resource) = self.update_spout_resource() else {
+        if !self.spout.receive_resource(&mut self.texture_resource) {
+            return false;
+        }
+
+        // The sender changed, the frame arrives in the recreated resource with the next update.
+        if self.spout.is_updated() {
+            self.recreate_spout_resource();
+            return false;
+        }
+
+        let Some(resource) = self.texture_resource else {
             return false;
         };
 
-        self.free_godot_resources();
-        self.update_godot_resources(resource);
-        true
+        if !self.rd_texture_rid.is_valid() {
+            self.create_rd_texture(resource);
+        }
+
+        self.rd_texture_rid.is_valid()
+    }
+
+    fn reset_resource(&mut self) {
+        // Frames are copied into the receiver texture on every update, so there is nothing to restore.
     }
 }
 
 impl D3D12SpoutReceiver {
-    fn update_spout_resource(&mut self) -> Option<NonNull<ID3D12Resource>> {
-        let success = self.spout.receive_resource(&mut self.texture_resource);
-
-        if !success || !self.spout.is_updated() {
-            return None;
-        }
+    fn recreate_spout_resource(&mut self) {
+        self.free_rd_texture();
 
         let Some(device) = get_d3d12_device() else {
-            godot_error!("Unable to obtain D3D12 Device.");
-            return None;
+            diagnostics::error(ErrorCode::NativeDeviceUnavailable, "Unable to obtain D3D12 Device.");
+            return;
         };
 
         self.spout.create_receiver_resource(device, &mut self.texture_resource);
 
-        let Some(texture) = self.texture_resource else {
-            godot_error!("Texture was null.");
-            return None;
-        };
-
-        Some(texture)
+        if self.texture_resource.is_none() {
+            diagnostics::error(ErrorCode::InvalidTexture, "Texture was null.");
+        }
     }
 
-    fn update_godot_resources(&mut self, texture: NonNull<ID3D12Resource>) {
-        let mut rendering_server = RenderingServer::singleton();
-        let Some(mut rendering_device) = rendering_server.get_rendering_device() else {
-            godot_error!("Rendering device was null.");
+    fn create_rd_texture(&mut self, texture: NonNull<ID3D12Resource>) {
+        let Some(mut rendering_device) = RenderingServer::singleton().get_rendering_device() else {
+            diagnostics::error(ErrorCode::RenderingDeviceUnavailable, "Rendering device was null.");
             return;
         };
 
-        let data_format = convert_dxgi_to_rd_data_format(self.spout.get_sender_format());
+        self.data_format = convert_dxgi_to_rd_data_format(self.spout.get_sender_format());
 
+        // Only ever copied from, the RenderingServer never gets to own the texture wrapping the Spout resource.
         self.rd_texture_rid = rendering_device.texture_create_from_extension(
             TextureType::TYPE_2D,
-            data_format,
+            self.data_format,
             TextureSamples::SAMPLES_1,
-            TextureUsageBits::SAMPLING_BIT,
+            TextureUsageBits::SAMPLING_BIT | TextureUsageBits::CAN_COPY_FROM_BIT,
             texture.as_ptr() as u64,
             self.spout.get_sender_width() as u64,
             self.spout.get_sender_height() as u64,
             0,
             1,
         );
-        self.rs_texture_rid = rendering_server.texture_rd_create(self.rd_texture_rid);
     }
 
-    fn free_godot_resources(&mut self) {
-        let mut rendering_server = RenderingServer::singleton();
-        let Some(mut rendering_device) = rendering_server.get_rendering_device() else {
-            godot_error!("Rendering device was null.");
-            return;
-        };
+    fn free_rd_texture(&mut self) {
+        let rd_texture_rid = std::mem::replace(&mut self.rd_texture_rid, Rid::Invalid);
 
-        if self.rs_texture_rid.is_valid() {
-            rendering_server.free_rid(self.rs_texture_rid);
-            self.rs_texture_rid = Rid::Invalid;
+        if !rd_texture_rid.is_valid() {
+            return;
         }
 
-        if self.rd_texture_rid.is_valid() {
-            rendering_device.free_rid(self.rd_texture_rid);
-            self.rd_texture_rid = Rid::Invalid;
-        }
+        let Some(mut rendering_device) = RenderingServer::singleton().get_rendering_device() else {
+            diagnostics::error(ErrorCode::RenderingDeviceUnavailable, "Rendering device was null.");
+            return;
+        };
+
+        rendering_device.free_rid(rd_texture_rid);
     }
 }
@@ -1,32 +1,16 @@
 use crate::spout::receiver::SpoutReceiver;
-use godot::builtin::Rid;
-use godot::classes::RenderingServer;
+use godot::classes::rendering_device::DataFormat;
+use godot::prelude::*;
 
-pub struct NoOpReceiver {
-    placeholder: Rid,
-}
-
-impl Drop for NoOpReceiver {
-    fn drop(&mut self) {
-        RenderingServer::singleton().free_rid(self.placeholder);
-    }
-}
+pub struct NoOpReceiver;
 
 impl NoOpReceiver {
     pub fn new() -> Box<dyn SpoutReceiver> {
-        let receiver = NoOpReceiver {
-            placeholder: RenderingServer::singleton().texture_2d_placeholder_create(),
-        };
-
-        Box::new(receiver)
+        Box::new(NoOpReceiver)
     }
 }
 
 impl SpoutReceiver for NoOpReceiver {
-    fn rid(&self) -> Rid {
-        self.placeholder
-    }
-
     fn set_sender_name(&mut self, _name: &str) {
         // No-op
     }
@@ -39,8 +23,28 @@ impl SpoutReceiver for NoOpReceiver {
         1
     }
 
+    fn format(&self) -> DataFormat {
+        DataFormat::MAX
+    }
+
+    fn is_connected(&self) -> bool {
+        false
+    }
+
+    fn frame(&self) -> i64 {
+        0
+    }
+
+    fn rd_texture(&self) -> Rid {
+        Rid::Invalid
+    }
+
     fn update_resource(&mut self) -> bool {
         // No-op
         false
     }
+
+    fn reset_resource(&mut self) {
+        // No-op
+    }
 }
@@ -0,0 +1,207 @@
+use crate::diagnostics;
+use crate::diagnostics::ErrorCode;
+use crate::monitors;
+use godot::classes::rendering_device::{DataFormat, TextureUsageBits};
+use godot::classes::{RdTextureView, RenderingDevice, RenderingServer};
+use godot::prelude::*;
+
+// Received frames are copied into a ring of `depth + 1` textures owned by the ring, and the slot holding the latest
+// frame is shown through the receiver texture in place of the display texture. Frames are never copied into the shown
+// slot, and a slot is only written again `depth` frames after it stopped being shown. The rendering device orders each
+// copy after the work sampling the slot before, so sampling never observes a partially written frame.
+pub struct FrameRing {
+    depth: usize,
+    slot_rids: Vec<Rid>,
+    size: Vector2i,
+    format: DataFormat,
+    mipmaps: u32,
+    usage: TextureUsageBits,
+    shown_slot: Option<usize>,
+}
+
+impl Drop for FrameRing {
+    fn drop(&mut self) {
+        let Some(mut rendering_device) = RenderingServer::singleton().get_rendering_device() else {
+            return;
+        };
+
+        free_slots(&mut rendering_device, &mut self.slot_rids);
+    }
+}
+
+impl FrameRing {
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            slot_rids: Vec::new(),
+            size: Vector2i::ZERO,
+            format: DataFormat::MAX,
+            mipmaps: 0,
+            usage: TextureUsageBits::SAMPLING_BIT,
+            shown_slot: None,
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    // Copies the source into the next slot and shows it through `texture_rid`. Returns whether the slots were recreated
+    // because the source changed its size or format.
+    pub fn present(&mut self, texture_rid: Rid, source_rid: Rid, usage: TextureUsageBits) -> bool {
+        let mut rendering_server = RenderingServer::singleton();
+        let Some(mut rendering_device) = rendering_server.get_rendering_device() else {
+            diagnostics::error(ErrorCode::RenderingDeviceUnavailable, "Rendering device was null.");
+            return false;
+        };
+
+        let Some(mut format) = rendering_device.texture_get_format(source_rid) else {
+            diagnostics::error(
+                ErrorCode::InvalidTexture,
+                "Unable to obtain the received texture format.",
+            );
+            return false;
+        };
+
+        let size = Vector2i::new(format.get_width() as i32, format.get_height() as i32);
+        let mipmaps = format.get_mipmaps().max(1);
+        let usage = usage
+            | TextureUsageBits::SAMPLING_BIT
+            | TextureUsageBits::CAN_COPY_TO_BIT
+            | TextureUsageBits::CAN_COPY_FROM_BIT;
+
+        let recreate = self.slot_rids.is_empty()
+            || self.size != size
+            || self.format != format.get_format()
+            || self.mipmaps != mipmaps
+            || self.usage != usage;
+
+        if recreate {
+            free_slots(&mut rendering_device, &mut self.slot_rids);
+            format.set_usage_bits(usage);
+
+            self.slot_rids = (0..slot_count(self.depth))
+                .map(|_| rendering_device.texture_create(&format, &RdTextureView::new_gd()))
+                .collect();
+
+            if !self.slot_rids.iter().all(Rid::is_valid) {
+                diagnostics::error(ErrorCode::InvalidTexture, "Failed to create the ring textures.");
+                free_slots(&mut rendering_device, &mut self.slot_rids);
+                return false;
+            }
+
+            self.size = size;
+            self.format = format.get_format();
+            self.mipmaps = mipmaps;
+            self.usage = usage;
+            self.shown_slot = None;
+        }
+
+        let slot = next_slot(self.shown_slot, self.slot_rids.len());
+        let slot_rid = self.slot_rids[slot];
+
+        for mipmap in 0..mipmaps {
+            let level_size = Vector2i::new((size.x >> mipmap).max(1), (size.y >> mipmap).max(1));
+
+            rendering_device.texture_copy(
+                source_rid,
+                slot_rid,
+                Vector3::ZERO,
+                Vector3::ZERO,
+                Vector3::new(level_size.x as f32, level_size.y as f32, 1.0),
+                mipmap,
+                mipmap,
+                0,
+                0,
+            );
+        }
+
+        monitors::bytes_copied(size.x as u64 * size.y as u64 * bytes_per_pixel(self.format));
+
+        // The RenderingServer owns and frees the shared view it is given, the slot itself stays owned by the ring.
+        let view_rid = rendering_device.texture_create_shared(&RdTextureView::new_gd(), slot_rid);
+        let rs_texture_rid = rendering_server.texture_rd_create(view_rid);
+        rendering_server.texture_replace(texture_rid, rs_texture_rid);
+        self.shown_slot = Some(slot);
+
+        recreate
+    }
+}
+
+fn slot_count(depth: usize) -> usize {
+    depth + 1
+}
+
+// Slots are written in turn, starting after the shown slot, so the shown slot is the one written last.
+fn next_slot(shown_slot: Option<usize>, slot_count: usize) -> usize {
+    shown_slot.map_or(0, |shown_slot| (shown_slot + 1) % slot_count)
+}
+
+fn free_slots(rendering_device: &mut Gd<RenderingDevice>, slot_rids: &mut Vec<Rid>) {
+    for slot_rid in slot_rids.drain(..) {
+        if rendering_device.texture_is_valid(slot_rid) {
+            rendering_device.free_rid(slot_rid);
+        }
+    }
+}
+
+// Only used for instrumentation, so formats a sender is unlikely to share are counted like 8 bit RGBA.
+fn bytes_per_pixel(format: DataFormat) -> u64 {
+    match format {
+        DataFormat::R8_UNORM => 1,
+        DataFormat::R8G8_UNORM => 2,
+        DataFormat::R16G16B16A16_SFLOAT => 8,
+        DataFormat::R32G32B32A32_SFLOAT => 16,
+        _ => 4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn written_slots(depth: usize, frames: usize) -> Vec<usize> {
+        let mut shown_slot = None;
+
+        (0..frames)
+            .map(|_| {
+                let slot = next_slot(shown_slot, slot_count(depth));
+                shown_slot = Some(slot);
+                slot
+            })
+            .collect()
+    }
+
+    #[test]
+    fn shown_slot_is_never_written() {
+        for depth in 1..=4 {
+            let slots = written_slots(depth, 16);
+
+            for frames in slots.windows(2) {
+                assert_ne!(frames[0], frames[1], "depth {depth}");
+            }
+        }
+    }
+
+    #[test]
+    fn slots_are_written_again_after_depth_frames() {
+        for depth in 1..=4 {
+            let slots = written_slots(depth, 16);
+
+            // A slot stops being shown one frame after it was written, and is written again `depth` frames later.
+            for (frame, slot) in slots.iter().enumerate() {
+                let next_write = slots[frame + 1..].iter().position(|next| next == slot);
+
+                if let Some(next_write) = next_write {
+                    assert_eq!(next_write, depth, "depth {depth}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ring_starts_at_first_slot() {
+        assert_eq!(next_slot(None, slot_count(2)), 0);
+        assert_eq!(written_slots(2, 4), vec![0, 1, 2, 0]);
+    }
+}
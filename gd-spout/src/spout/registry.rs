@@ -0,0 +1,216 @@
+use std::cmp::Reverse;
+use std::env::consts::OS;
+use std::error::Error;
+
+use crate::diagnostics;
+use crate::diagnostics::ErrorCode;
+use crate::settings;
+
+pub type Probe = fn() -> Result<(), Box<dyn Error>>;
+
+// A backend declares where it can run, empty lists accept any driver or platform. The probe checks what can only be
+// known at runtime, before the backend is created with `create`.
+pub struct Backend<C> {
+    pub name: &'static str,
+    pub drivers: &'static [&'static str],
+    pub platforms: &'static [&'static str],
+    pub priority: i32,
+    pub probe: Probe,
+    pub create: C,
+}
+
+impl<C> Backend<C> {
+    fn check(&self, driver_name: &str) -> Result<(), Box<dyn Error>> {
+        if !self.platforms.is_empty() && !self.platforms.contains(&OS) {
+            return Err(format!("Unsupported platform: {OS}").into());
+        }
+
+        if !self.drivers.is_empty() && !self.drivers.contains(&driver_name) {
+            return Err(format!("Unsupported rendering driver: {driver_name}").into());
+        }
+
+        (self.probe)()
+    }
+}
+
+pub fn always_available() -> Result<(), Box<dyn Error>> {
+    Ok(())
+}
+
+// Creates the first backend that supports the driver, starting with the preferred backend from the project settings and
+// continuing from the highest priority down. Backends that support the driver but fail are reported right away, as a
+// lower priority backend is used instead. The backends skipped before are listed with the one used, and when no backend
+// could be created, all rejections are returned.
+pub fn select<C: Copy, T>(
+    kind: &str,
+    backends: &[Backend<C>],
+    driver_name: &str,
+    create: impl Fn(C) -> Result<T, Box<dyn Error>>,
+) -> Result<T, String> {
+    let preferred_backend = settings::get().preferred_backend;
+    select_preferring(kind, backends, driver_name, preferred_backend.as_deref(), create)
+}
+
+fn select_preferring<C: Copy, T>(
+    kind: &str,
+    backends: &[Backend<C>],
+    driver_name: &str,
+    preferred_backend: Option<&str>,
+    create: impl Fn(C) -> Result<T, Box<dyn Error>>,
+) -> Result<T, String> {
+    let mut candidates: Vec<&Backend<C>> = backends.iter().collect();
+    candidates.sort_by_key(|backend| (Some(backend.name) != preferred_backend, Reverse(backend.priority)));
+
+    let mut rejections = Vec::new();
+
+    for backend in candidates {
+        if let Err(err) = backend.check(driver_name) {
+            rejections.push(format!("{}: {err}", backend.name));
+            continue;
+        }
+
+        match create(backend.create) {
+            Ok(created) => {
+                let mut message = format!("Using {kind} backend: {}", backend.name);
+
+                if !rejections.is_empty() {
+                    message = format!("{message}; Skipped: {}", rejections.join("; "));
+                }
+
+                diagnostics::verbose(ErrorCode::BackendSelected, message);
+
+                return Ok(created);
+            }
+            Err(err) => {
+                diagnostics::warning(
+                    ErrorCode::BackendFailed,
+                    format!("{err}; Failed to create {kind} backend: {}", backend.name),
+                );
+
+                rejections.push(format!("{}: {err}", backend.name));
+            }
+        }
+    }
+
+    Err(rejections.join("; "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::Diagnostics;
+
+    fn unavailable() -> Result<(), Box<dyn Error>> {
+        Err("Probe failed".into())
+    }
+
+    fn backend(name: &'static str, drivers: &'static [&'static str], priority: i32) -> Backend<&'static str> {
+        Backend {
+            name,
+            drivers,
+            platforms: &[],
+            priority,
+            probe: always_available,
+            create: name,
+        }
+    }
+
+    // Backends named "broken" fail to be created. Diagnostics go to an endpoint, so nothing is logged to Godot. The message
+    // naming the selected backend is returned along with it.
+    fn select_logged(
+        backends: &[Backend<&'static str>],
+        driver_name: &str,
+        preferred_backend: Option<&str>,
+    ) -> (Result<&'static str, String>, Option<String>) {
+        let endpoint = Diagnostics::default();
+
+        let selected = diagnostics::with_endpoint(&endpoint, || {
+            select_preferring("test", backends, driver_name, preferred_backend, |name| match name {
+                "broken" => Err("Creation failed".into()),
+                name => Ok(name),
+            })
+        });
+
+        let message = endpoint
+            .take_pending()
+            .into_iter()
+            .find(|diagnostic| diagnostic.code == ErrorCode::BackendSelected)
+            .map(|diagnostic| diagnostic.message);
+
+        (selected, message)
+    }
+
+    fn select_name(
+        backends: &[Backend<&'static str>],
+        driver_name: &str,
+        preferred_backend: Option<&str>,
+    ) -> Result<&'static str, String> {
+        select_logged(backends, driver_name, preferred_backend).0
+    }
+
+    #[test]
+    fn highest_priority_is_selected() {
+        let backends = [
+            backend("low", &[], 0),
+            backend("high", &[], 100),
+            backend("middle", &[], 50),
+        ];
+        assert_eq!(
+            select_logged(&backends, "vulkan", None),
+            (Ok("high"), Some("Using test backend: high".to_owned()))
+        );
+    }
+
+    #[test]
+    fn preferred_backend_comes_first() {
+        let backends = [backend("low", &[], 0), backend("high", &[], 100)];
+        assert_eq!(select_name(&backends, "vulkan", Some("low")), Ok("low"));
+        assert_eq!(select_name(&backends, "vulkan", Some("missing")), Ok("high"));
+    }
+
+    #[test]
+    fn unsupported_and_failing_backends_are_skipped() {
+        let mut unavailable_backend = backend("unavailable", &[], 300);
+        unavailable_backend.probe = unavailable;
+
+        let backends = [
+            backend("d3d12 only", &["d3d12"], 400),
+            unavailable_backend,
+            backend("broken", &[], 200),
+            backend("fallback", &[], 0),
+        ];
+
+        assert_eq!(
+            select_logged(&backends, "vulkan", None),
+            (
+                Ok("fallback"),
+                Some(
+                    "Using test backend: fallback; Skipped: d3d12 only: Unsupported rendering driver: vulkan; \
+                     unavailable: Probe failed; broken: Creation failed"
+                        .to_owned()
+                )
+            )
+        );
+        assert_eq!(select_name(&backends, "d3d12", None), Ok("d3d12 only"));
+    }
+
+    #[test]
+    fn rejections_are_listed_in_order() {
+        let mut unavailable_backend = backend("unavailable", &[], 100);
+        unavailable_backend.probe = unavailable;
+
+        let backends = [
+            backend("broken", &[], 50),
+            unavailable_backend,
+            backend("d3d12 only", &["d3d12"], 0),
+        ];
+
+        assert_eq!(
+            select_name(&backends, "vulkan", None),
+            Err(
+                "unavailable: Probe failed; broken: Creation failed; d3d12 only: Unsupported rendering driver: vulkan"
+                    .to_owned()
+            )
+        );
+    }
+}
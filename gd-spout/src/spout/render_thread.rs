@@ -0,0 +1,26 @@
+use crate::diagnostics;
+use crate::diagnostics::Diagnostics;
+use godot::classes::RenderingServer;
+use godot::prelude::*;
+use std::sync::Mutex;
+
+// Runs the given task on the rendering thread. When the RenderingServer is not running on a separate thread, the task
+// is run immediately on the calling thread.
+pub fn call_on_render_thread(task: impl FnOnce() + Send + 'static) {
+    let task = Mutex::new(Some(task));
+    let callable = Callable::from_sync_fn("gd_spout_render_task", move |_args| {
+        if let Some(task) = task.lock().ok().and_then(|mut task| task.take()) {
+            task();
+        }
+
+        Ok(Variant::nil())
+    });
+
+    RenderingServer::singleton().call_on_render_thread(&callable);
+}
+
+// Like `call_on_render_thread`, attributing everything the task reports to the given endpoint.
+pub fn call_on_render_thread_for(diagnostics: &Diagnostics, task: impl FnOnce() + Send + 'static) {
+    let diagnostics = diagnostics.clone();
+    call_on_render_thread(move || diagnostics::with_endpoint(&diagnostics, task));
+}
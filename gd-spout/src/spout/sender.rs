@@ -1,24 +1,189 @@
+use crate::diagnostics;
+use crate::diagnostics::{Diagnostics, ErrorCode};
+use crate::monitors;
+use crate::spout::registry;
+use crate::spout::registry::Backend;
+use crate::spout::render_thread::call_on_render_thread_for;
+use godot::classes::RenderingServer;
 use godot::prelude::*;
 use std::error::Error;
+use std::sync::{Arc, Mutex};
 
+mod cpu;
 #[cfg(target_os = "windows")]
 mod dx12;
 mod no_op;
 
-pub trait SpoutSender {
+pub trait SpoutSender: Send {
     fn set_sender_name(&mut self, name: &str);
-    fn send_resource(&mut self, resource: Rid);
+    fn set_async_readback(&mut self, async_readback: bool);
+    fn send_resource(&mut self, texture: SenderTexture);
 }
 
+// Sent textures are either RenderingServer textures or textures created directly on the RenderingDevice, backends
+// resolve whichever they need on the rendering thread.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SenderTexture {
+    Texture(Rid),
+    RdTexture(Rid),
+}
+
+impl SenderTexture {
+    pub fn rd_texture(self) -> Rid {
+        match self {
+            SenderTexture::Texture(rid) => RenderingServer::singleton().texture_get_rd_texture(rid),
+            SenderTexture::RdTexture(rid) => rid,
+        }
+    }
+}
+
+type CreateSender = fn() -> Result<Box<dyn SpoutSender>, Box<dyn Error>>;
+
+const BACKENDS: &[Backend<CreateSender>] = &[
+    #[cfg(target_os = "windows")]
+    Backend {
+        name: "dx12",
+        drivers: &["d3d12"],
+        platforms: &["windows"],
+        priority: 100,
+        probe: crate::spout::d3d12_util::probe,
+        create: dx12::D3D12SpoutSender::new,
+    },
+    // Frames are read back and written to the frame store for CPU receivers, which stalls on every frame, so it is the
+    // last resort, e.g. for the Compatibility renderer.
+    Backend {
+        name: "cpu",
+        drivers: &[],
+        platforms: &[],
+        priority: 0,
+        probe: registry::always_available,
+        create: cpu::CpuSpoutSender::new,
+    },
+];
+
 pub fn create_sender(driver_name: &str) -> Box<dyn SpoutSender> {
-    let receiver = match driver_name {
-        #[cfg(target_os = "windows")]
-        "d3d12" => dx12::D3D12SpoutSender::new(),
-        _ => Ok(no_op::NoOpSender::new()),
-    };
-
-    receiver.unwrap_or_else(|err: Box<dyn Error>| {
-        godot_error!("{err}; Failed to create sender: {driver_name}; Falling back on no op implementation.");
+    registry::select("sender", BACKENDS, driver_name, |create| create()).unwrap_or_else(|rejections| {
+        diagnostics::error(
+            ErrorCode::BackendUnavailable,
+            format!("{rejections}; Failed to create sender: {driver_name}; Falling back on no op implementation."),
+        );
         no_op::NoOpSender::new()
     })
 }
+
+// The backend is created, used and released on the rendering thread. Settings are handed off from the owning thread and
+// applied before the next send.
+#[derive(Clone)]
+pub struct SenderHandle {
+    state: Arc<Mutex<SenderState>>,
+    diagnostics: Diagnostics,
+}
+
+struct SenderState {
+    backend: Option<Box<dyn SpoutSender>>,
+    pending_name: Option<String>,
+    pending_async_readback: Option<bool>,
+}
+
+impl SenderHandle {
+    pub fn new(name: &str) -> Self {
+        let state = Arc::new(Mutex::new(SenderState {
+            backend: None,
+            pending_name: Some(name.to_owned()),
+            pending_async_readback: None,
+        }));
+
+        let diagnostics = Diagnostics::default();
+        let task_state = state.clone();
+        call_on_render_thread_for(&diagnostics, move || {
+            let driver_name = RenderingServer::singleton()
+                .get_current_rendering_driver_name()
+                .to_string();
+
+            let backend = create_sender(&driver_name);
+
+            if let Ok(mut state) = task_state.lock() {
+                state.backend = Some(backend);
+            }
+        });
+
+        Self { state, diagnostics }
+    }
+
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    pub fn set_sender_name(&self, name: &str) {
+        if let Ok(mut state) = self.state.lock() {
+            state.pending_name = Some(name.to_owned());
+        }
+    }
+
+    pub fn set_async_readback(&self, async_readback: bool) {
+        if let Ok(mut state) = self.state.lock() {
+            state.pending_async_readback = Some(async_readback);
+        }
+    }
+
+    pub fn send_resource(&self, texture: SenderTexture) {
+        self.send(move || Some(texture));
+    }
+
+    // Textures replaced by their owner on the rendering thread are looked up when the frame is sent, so a replaced
+    // texture is never sent after it was freed.
+    pub fn send_shared_rd_texture(&self, rid: Arc<Mutex<Rid>>) {
+        self.send(move || {
+            rid.lock()
+                .ok()
+                .map(|rid| *rid)
+                .filter(Rid::is_valid)
+                .map(SenderTexture::RdTexture)
+        });
+    }
+
+    fn send(&self, texture: impl FnOnce() -> Option<SenderTexture> + Send + 'static) {
+        let state = self.state.clone();
+
+        call_on_render_thread_for(&self.diagnostics, move || {
+            let Ok(mut state) = state.lock() else {
+                return;
+            };
+
+            let SenderState {
+                backend,
+                pending_name,
+                pending_async_readback,
+            } = &mut *state;
+
+            let Some(backend) = backend else {
+                return;
+            };
+
+            if let Some(name) = pending_name.take() {
+                backend.set_sender_name(&name);
+            }
+
+            if let Some(async_readback) = pending_async_readback.take() {
+                backend.set_async_readback(async_readback);
+            }
+
+            let Some(texture) = texture() else {
+                return;
+            };
+
+            monitors::time_send(|| backend.send_resource(texture));
+            monitors::frame_sent();
+        });
+    }
+
+    pub fn release(&self) {
+        let state = self.state.clone();
+
+        call_on_render_thread_for(&self.diagnostics, move || {
+            if let Ok(mut state) = state.lock() {
+                state.backend = None;
+            }
+        });
+    }
+}
@@ -0,0 +1,188 @@
+use crate::diagnostics;
+use crate::diagnostics::ErrorCode;
+use crate::monitors;
+use crate::spout::frame_store::{FrameHeader, FrameStore};
+use crate::spout::receiver::image_data;
+use crate::spout::sender::{SenderTexture, SpoutSender};
+use godot::classes::image::Format as ImageFormat;
+use godot::classes::rendering_device::DataFormat;
+use godot::classes::{Image, RenderingServer};
+use godot::prelude::*;
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+
+// Reads frames back to the CPU and publishes them to the frame store for CPU receivers. The RenderingServer readback
+// works with every renderer, including Compatibility, but stalls until the frame is rendered. Asynchronous readback
+// avoids the stall by publishing frames a few frames later, which requires a RenderingDevice.
+pub struct CpuSpoutSender {
+    // Shared with pending asynchronous readbacks, which publish under the current name. `None` once the sender is
+    // dropped, so late readbacks don't publish a frame again.
+    name: Arc<Mutex<Option<String>>>,
+    frame: u64,
+    async_readback: bool,
+}
+
+impl Drop for CpuSpoutSender {
+    fn drop(&mut self) {
+        if let Some(name) = self.name.lock().ok().and_then(|mut name| name.take()) {
+            FrameStore::shared().clear_active_sender(&name);
+            FrameStore::shared().remove_frame(&name);
+        }
+    }
+}
+
+impl CpuSpoutSender {
+    pub fn new() -> Result<Box<dyn SpoutSender>, Box<dyn Error>> {
+        Ok(Box::new(Self {
+            name: Arc::new(Mutex::new(Some(String::new()))),
+            frame: 0,
+            async_readback: false,
+        }))
+    }
+
+    fn read_texture(texture: SenderTexture) -> Option<Gd<Image>> {
+        match texture {
+            SenderTexture::Texture(rid) => RenderingServer::singleton().texture_2d_get(rid),
+            SenderTexture::RdTexture(rid) => {
+                let mut rendering_device = RenderingServer::singleton().get_rendering_device()?;
+                let texture_format = rendering_device.texture_get_format(rid)?;
+                let data = rendering_device.texture_get_data(rid, 0);
+
+                create_image(
+                    texture_format.get_width() as i32,
+                    texture_format.get_height() as i32,
+                    texture_format.get_format(),
+                    &data,
+                )
+            }
+        }
+    }
+
+    fn request_readback(&self, texture: SenderTexture) {
+        let Some(mut rendering_device) = RenderingServer::singleton().get_rendering_device() else {
+            return;
+        };
+
+        let rd_texture_rid = texture.rd_texture();
+        let Some(texture_format) = rendering_device.texture_get_format(rd_texture_rid) else {
+            diagnostics::error(ErrorCode::InvalidTexture, "Unable to obtain the sent texture format.");
+            return;
+        };
+
+        let width = texture_format.get_width() as i32;
+        let height = texture_format.get_height() as i32;
+        let format = texture_format.get_format();
+        let frame = self.frame;
+        let name = self.name.clone();
+
+        let callback = Callable::from_sync_fn("gd_spout_send_readback", move |args| {
+            let data = args
+                .first()
+                .and_then(|data| data.try_to::<PackedByteArray>().ok())
+                .unwrap_or_default();
+
+            if let Some(image) = create_image(width, height, format, &data) {
+                if let Some(name) = name.lock().ok().and_then(|name| name.clone()) {
+                    publish(&name, frame, image);
+                }
+            }
+
+            Ok(Variant::nil())
+        });
+
+        rendering_device.texture_get_data_async(rd_texture_rid, 0, &callback);
+    }
+}
+
+impl SpoutSender for CpuSpoutSender {
+    fn set_sender_name(&mut self, name: &str) {
+        let Ok(mut current_name) = self.name.lock() else {
+            return;
+        };
+
+        if let Some(previous_name) = current_name.replace(name.to_owned()) {
+            FrameStore::shared().clear_active_sender(&previous_name);
+            FrameStore::shared().remove_frame(&previous_name);
+        }
+
+        if name.is_empty() {
+            return;
+        }
+
+        // Like Spout, the sender started last becomes the active sender.
+        if let Err(err) = FrameStore::shared().set_active_sender(name) {
+            diagnostics::warning(
+                ErrorCode::PublishFailed,
+                format!("{err}; Failed to make the sender active: {name}"),
+            );
+        }
+    }
+
+    fn set_async_readback(&mut self, async_readback: bool) {
+        self.async_readback = async_readback;
+    }
+
+    fn send_resource(&mut self, texture: SenderTexture) {
+        self.frame += 1;
+
+        // Without a RenderingDevice, as with the Compatibility renderer, readback is always synchronous.
+        if self.async_readback && RenderingServer::singleton().get_rendering_device().is_some() {
+            self.request_readback(texture);
+            return;
+        }
+
+        let Some(image) = Self::read_texture(texture) else {
+            diagnostics::error(ErrorCode::ReadbackFailed, "Unable to read back the sent texture.");
+            return;
+        };
+
+        if let Some(name) = self.name.lock().ok().and_then(|name| name.clone()) {
+            publish(&name, self.frame, image);
+        }
+    }
+}
+
+fn create_image(width: i32, height: i32, format: DataFormat, data: &PackedByteArray) -> Option<Gd<Image>> {
+    let Some((image_format, data)) = image_data(format, data.as_slice()) else {
+        diagnostics::error(
+            ErrorCode::UnsupportedFormat,
+            format!("Unsupported texture format for CPU sending: {format:?}"),
+        );
+        return None;
+    };
+
+    let data = PackedByteArray::from(data.as_ref());
+    Image::create_from_data(width, height, false, image_format, &data)
+}
+
+fn publish(name: &str, frame: u64, mut image: Gd<Image>) {
+    if name.is_empty() {
+        return;
+    }
+
+    if image.is_compressed() {
+        image.decompress();
+    }
+
+    image.clear_mipmaps();
+
+    if image.get_format() != ImageFormat::RGBA8 {
+        image.convert(ImageFormat::RGBA8);
+    }
+
+    let header = FrameHeader {
+        width: image.get_width() as u32,
+        height: image.get_height() as u32,
+        frame,
+    };
+
+    let data = image.get_data();
+    monitors::bytes_copied(data.len() as u64);
+
+    if let Err(err) = FrameStore::shared().write_frame(name, &header, data.as_slice()) {
+        diagnostics::error(
+            ErrorCode::PublishFailed,
+            format!("{err}; Failed to publish frame: {name}"),
+        );
+    }
+}
@@ -1,6 +1,8 @@
+use crate::diagnostics;
+use crate::diagnostics::ErrorCode;
 use crate::spout::d3d12_util;
 use crate::spout::d3d12_util::{get_d3d12_device, get_d3d12_command_queue};
-use crate::spout::sender::SpoutSender;
+use crate::spout::sender::{SenderTexture, SpoutSender};
 use godot::prelude::*;
 use spout_sys::SpoutDX12;
 
@@ -8,6 +10,9 @@ pub struct D3D12SpoutSender {
     spout: SpoutDX12,
 }
 
+// SAFETY: The backend is only ever accessed from the rendering thread through a `SenderHandle`.
+unsafe impl Send for D3D12SpoutSender {}
+
 impl Drop for D3D12SpoutSender {
     fn drop(&mut self) {
         self.spout.release_sender()
@@ -33,13 +38,17 @@ impl D3D12SpoutSender {
 impl SpoutSender for D3D12SpoutSender {
     fn set_sender_name(&mut self, name: &str) {
         if !self.spout.set_sender_name(name) {
-            godot_error!("Unable to set sender name.");
+            diagnostics::error(ErrorCode::SenderNameRejected, "Unable to set sender name.");
         }
     }
 
-    fn send_resource(&mut self, resource: Rid) {
-        let Some(resource) = d3d12_util::get_d3d12_resource_from_texture(resource) else {
-            godot_error!("Given RID returned invalid D3D12 resource.");
+    fn set_async_readback(&mut self, _async_readback: bool) {
+        // Frames are shared on the GPU, there is nothing to read back.
+    }
+
+    fn send_resource(&mut self, texture: SenderTexture) {
+        let Some(resource) = d3d12_util::get_d3d12_resource_from_texture(texture.rd_texture()) else {
+            diagnostics::error(ErrorCode::InvalidTexture, "Given RID returned invalid D3D12 resource.");
             return;
         };
 
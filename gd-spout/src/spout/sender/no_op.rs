@@ -1,5 +1,4 @@
-﻿use crate::spout::sender::SpoutSender;
-use godot::builtin::Rid;
+﻿use crate::spout::sender::{SenderTexture, SpoutSender};
 
 pub struct NoOpSender;
 
@@ -12,5 +11,7 @@ impl NoOpSender {
 impl SpoutSender for NoOpSender {
     fn set_sender_name(&mut self, _name: &str) {}
 
-    fn send_resource(&mut self, _resource: Rid) {}
+    fn set_async_readback(&mut self, _async_readback: bool) {}
+
+    fn send_resource(&mut self, _texture: SenderTexture) {}
 }
@@ -0,0 +1,248 @@
+use crate::diagnostics;
+use crate::diagnostics::ErrorCode;
+use crate::spout::compute::{compile_shader, dispatch};
+use godot::classes::environment::ToneMapper;
+use godot::classes::rendering_device::UniformType;
+use godot::classes::{RdSamplerState, RdUniform, RenderingDevice, RenderingServer};
+use godot::obj::EngineEnum;
+use godot::prelude::*;
+
+const TONEMAP_SHADER_SOURCE: &str = r#"
+#version 450
+
+layout(local_size_x = 8, local_size_y = 8, local_size_z = 1) in;
+
+layout(set = 0, binding = 0) uniform sampler2D source_texture;
+layout(set = 0, binding = 1, IMAGE_FORMAT) uniform restrict writeonly image2D target_image;
+
+layout(push_constant, std430) uniform Params {
+    ivec2 size;
+    int mode;
+    float exposure;
+    float white;
+    int pad0;
+    int pad1;
+    int pad2;
+} params;
+
+SRGB_FUNCTIONS
+
+// The tonemappers of Godot's tonemap pass, in the order of `Environment.ToneMapper`.
+vec3 tonemap_reinhard(vec3 color, float white) {
+    return (white * color + color) / (color * white + white);
+}
+
+vec3 tonemap_filmic(vec3 color, float white) {
+    const float exposure_bias = 2.0;
+    const float A = 0.22 * exposure_bias * exposure_bias;
+    const float B = 0.30 * exposure_bias;
+    const float C = 0.10;
+    const float D = 0.20;
+    const float E = 0.01;
+    const float F = 0.30;
+
+    vec3 color_tonemapped = ((color * (A * color + C * B) + D * E) / (color * (A * color + B) + D * F)) - E / F;
+    float white_tonemapped = ((white * (A * white + C * B) + D * E) / (white * (A * white + B) + D * F)) - E / F;
+
+    return color_tonemapped / white_tonemapped;
+}
+
+vec3 tonemap_aces(vec3 color, float white) {
+    const float exposure_bias = 1.8;
+    const float A = 0.0245786;
+    const float B = 0.000090537;
+    const float C = 0.983729;
+    const float D = 0.432951;
+    const float E = 0.238081;
+
+    const mat3 rgb_to_rrt = mat3(
+            vec3(0.59719 * exposure_bias, 0.35458 * exposure_bias, 0.04823 * exposure_bias),
+            vec3(0.07600 * exposure_bias, 0.90834 * exposure_bias, 0.01566 * exposure_bias),
+            vec3(0.02840 * exposure_bias, 0.13383 * exposure_bias, 0.83777 * exposure_bias));
+
+    const mat3 odt_to_rgb = mat3(
+            vec3(1.60475, -0.53108, -0.07367),
+            vec3(-0.10208, 1.10813, -0.00605),
+            vec3(-0.00327, -0.07276, 1.07602));
+
+    color *= rgb_to_rrt;
+    vec3 color_tonemapped = (color * (color + A) - B) / (color * (C * color + D) + E);
+    color_tonemapped *= odt_to_rgb;
+
+    white *= exposure_bias;
+    float white_tonemapped = (white * (white + A) - B) / (white * (C * white + D) + E);
+
+    return color_tonemapped / white_tonemapped;
+}
+
+// Minimal AgX, an approximation of the curve Godot uses.
+vec3 agx_contrast_approx(vec3 x) {
+    vec3 x2 = x * x;
+    vec3 x4 = x2 * x2;
+
+    return 15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 + 0.1191 * x - 0.00232;
+}
+
+vec3 tonemap_agx(vec3 color) {
+    const mat3 agx_inset = mat3(
+            vec3(0.842479062253094, 0.0423282422610123, 0.0423756549057051),
+            vec3(0.0784335999999992, 0.878468636469772, 0.0784336),
+            vec3(0.0792237451477643, 0.0791661274605434, 0.879142973793104));
+
+    const mat3 agx_outset = mat3(
+            vec3(1.19687900512017, -0.0528968517574562, -0.0529716355144438),
+            vec3(-0.0980208811401368, 1.15190312990417, -0.0980434501171241),
+            vec3(-0.0990297440797205, -0.0989611768448433, 1.15107367264116));
+
+    const float min_ev = -12.47393;
+    const float max_ev = 4.026069;
+
+    color = agx_inset * max(color, vec3(1e-10));
+    color = (clamp(log2(color), min_ev, max_ev) - min_ev) / (max_ev - min_ev);
+    color = agx_contrast_approx(color);
+    color = agx_outset * color;
+
+    // The curve produces display encoded values, decoded here as they are encoded again below.
+    return srgb_to_linear(clamp(color, 0.0, 1.0));
+}
+
+void main() {
+    ivec2 coord = ivec2(gl_GlobalInvocationID.xy);
+
+    if (any(greaterThanEqual(coord, params.size))) {
+        return;
+    }
+
+    vec4 color = texelFetch(source_texture, coord, 0);
+    color.rgb *= params.exposure;
+
+    if (params.mode == 1) {
+        color.rgb = tonemap_reinhard(max(color.rgb, vec3(0.0)), params.white);
+    } else if (params.mode == 2) {
+        color.rgb = tonemap_filmic(max(color.rgb, vec3(0.0)), params.white);
+    } else if (params.mode == 3) {
+        color.rgb = tonemap_aces(max(color.rgb, vec3(0.0)), params.white);
+    } else if (params.mode == 4) {
+        color.rgb = tonemap_agx(color.rgb);
+    }
+
+    imageStore(target_image, coord, vec4(linear_to_srgb(clamp(color.rgb, 0.0, 1.0)), clamp(color.a, 0.0, 1.0)));
+}
+"#;
+
+#[derive(Clone, Copy, Debug)]
+pub struct TonemapSettings {
+    pub mode: ToneMapper,
+    pub exposure: f32,
+    pub white: f32,
+}
+
+// Applies a tonemapper to the linear HDR color buffer with a compute pass, the way Godot's tonemap pass does before the
+// frame is shown. Glow, adjustments and color correction of the environment are not applied.
+pub struct Tonemapper {
+    shader_rid: Rid,
+    pipeline_rid: Rid,
+    sampler_rid: Rid,
+    uniform_set_rid: Rid,
+    uniform_set_textures: (Rid, Rid),
+}
+
+impl Drop for Tonemapper {
+    fn drop(&mut self) {
+        let Some(mut rendering_device) = RenderingServer::singleton().get_rendering_device() else {
+            return;
+        };
+
+        // The pipeline and uniform set are freed along with the shader.
+        for rid in [self.sampler_rid, self.shader_rid] {
+            if rid.is_valid() {
+                rendering_device.free_rid(rid);
+            }
+        }
+    }
+}
+
+impl Tonemapper {
+    pub fn new(rendering_device: &mut Gd<RenderingDevice>) -> Option<Self> {
+        let shader_rid = compile_shader(rendering_device, TONEMAP_SHADER_SOURCE, "rgba8", "tonemap")?;
+
+        Some(Self {
+            shader_rid,
+            pipeline_rid: rendering_device.compute_pipeline_create(shader_rid),
+            sampler_rid: rendering_device.sampler_create(&RdSamplerState::new_gd()),
+            uniform_set_rid: Rid::Invalid,
+            uniform_set_textures: (Rid::Invalid, Rid::Invalid),
+        })
+    }
+
+    // Writes the tonemapped source into the target, an `R8G8B8A8_UNORM` storage texture of the same size that ends up
+    // sRGB encoded like the screen.
+    pub fn apply(
+        &mut self,
+        rendering_device: &mut Gd<RenderingDevice>,
+        source_rid: Rid,
+        target_rid: Rid,
+        size: Vector2i,
+        settings: TonemapSettings,
+    ) {
+        if !self.update_uniform_set(rendering_device, source_rid, target_rid) {
+            return;
+        }
+
+        let compute_list = rendering_device.compute_list_begin();
+        rendering_device.compute_list_bind_compute_pipeline(compute_list, self.pipeline_rid);
+        rendering_device.compute_list_bind_uniform_set(compute_list, self.uniform_set_rid, 0);
+        dispatch(
+            rendering_device,
+            compute_list,
+            size,
+            &[
+                size.x,
+                size.y,
+                settings.mode.ord(),
+                settings.exposure.to_bits() as i32,
+                settings.white.to_bits() as i32,
+                0,
+                0,
+                0,
+            ],
+        );
+        rendering_device.compute_list_end();
+    }
+
+    fn update_uniform_set(
+        &mut self,
+        rendering_device: &mut Gd<RenderingDevice>,
+        source_rid: Rid,
+        target_rid: Rid,
+    ) -> bool {
+        // Uniform sets are invalidated when either texture is freed, such as when the viewport is resized.
+        if self.uniform_set_textures == (source_rid, target_rid)
+            && rendering_device.uniform_set_is_valid(self.uniform_set_rid)
+        {
+            return true;
+        }
+
+        let mut source = RdUniform::new_gd();
+        source.set_uniform_type(UniformType::SAMPLER_WITH_TEXTURE);
+        source.set_binding(0);
+        source.add_id(self.sampler_rid);
+        source.add_id(source_rid);
+
+        let mut target = RdUniform::new_gd();
+        target.set_uniform_type(UniformType::IMAGE);
+        target.set_binding(1);
+        target.add_id(target_rid);
+
+        self.uniform_set_rid =
+            rendering_device.uniform_set_create(&[source, target].into_iter().collect(), self.shader_rid, 0);
+        self.uniform_set_textures = (source_rid, target_rid);
+
+        if !self.uniform_set_rid.is_valid() {
+            diagnostics::error(ErrorCode::ConversionFailed, "Failed to create tonemap uniform set.");
+            return false;
+        }
+
+        true
+    }
+}
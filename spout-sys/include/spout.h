@@ -5,6 +5,7 @@
 #include <d3d12.h>
 #include <d3d11.h>
 #include <wrl.h>
+#include "rust/cxx.h"
 
 struct spoutDX12;
 
@@ -37,6 +38,10 @@ public:
 
     bool is_updated() const;
 
+    bool is_connected() const;
+
+    int64_t get_sender_frame() const;
+
     bool receive_resource(ID3D12Resource **resource) const;
 
     bool create_receiver_resource(ID3D12Device *device, ID3D12Resource **resource) const;
@@ -54,3 +59,9 @@ private:
 
 std::unique_ptr<SpoutDX12> new_spout_dx12(ID3D12Device *device);
 std::unique_ptr<SpoutDX12> new_spout_dx12_with_queue(ID3D12Device *device, ID3D12CommandQueue *commandQueue);
+
+rust::Vec<rust::String> get_sender_names();
+
+rust::String get_active_sender();
+
+rust::String get_sender_host_path(const std::string &name);
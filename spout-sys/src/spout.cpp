@@ -117,6 +117,14 @@ bool SpoutDX12::is_updated() const {
     return _spout->IsUpdated();
 }
 
+bool SpoutDX12::is_connected() const {
+    return _spout->IsConnected();
+}
+
+int64_t SpoutDX12::get_sender_frame() const {
+    return _spout->GetSenderFrame();
+}
+
 bool SpoutDX12::receive_resource(ID3D12Resource **resource) const {
     return _spout->ReceiveDX12Resource(resource);
 }
@@ -139,3 +147,42 @@ std::unique_ptr<SpoutDX12> new_spout_dx12(ID3D12Device *device) {
 std::unique_ptr<SpoutDX12> new_spout_dx12_with_queue(ID3D12Device *device, ID3D12CommandQueue *commandQueue) {
     return std::make_unique<SpoutDX12>(device, commandQueue);
 }
+
+rust::Vec<rust::String> get_sender_names() {
+    rust::Vec<rust::String> names;
+    spoutSenderNames senderNames;
+    char name[256];
+
+    int count = senderNames.GetSenderCount();
+    for (int i = 0; i < count; i++) {
+        if (senderNames.GetSender(i, name, 256)) {
+            names.push_back(rust::String(name));
+        }
+    }
+
+    return names;
+}
+
+rust::String get_active_sender() {
+    spoutSenderNames senderNames;
+    char name[256];
+
+    if (!senderNames.GetActiveSender(name)) {
+        return rust::String();
+    }
+
+    return rust::String(name);
+}
+
+// Senders record the path of their executable in the description of their shared texture info.
+rust::String get_sender_host_path(const std::string &name) {
+    spoutSenderNames senderNames;
+    SharedTextureInfo info;
+
+    if (!senderNames.getSharedInfo(name.c_str(), &info)) {
+        return rust::String();
+    }
+
+    const char *description = reinterpret_cast<const char *>(info.description);
+    return rust::String(description, strnlen(description, sizeof(info.description)));
+}
@@ -153,6 +153,12 @@ mod ffi {
         fn get_sender_height(self: &SpoutDX12) -> u32;
         fn get_sender_format(self: &SpoutDX12) -> DXGI_FORMAT;
         fn is_updated(self: &SpoutDX12) -> bool;
+        fn is_connected(self: &SpoutDX12) -> bool;
+        fn get_sender_frame(self: &SpoutDX12) -> i64;
+
+        fn get_sender_names() -> Vec<String>;
+        fn get_active_sender() -> String;
+        fn get_sender_host_path(name: &CxxString) -> String;
 
         unsafe fn new_spout_dx12(device: *mut ID3D12Device) -> UniquePtr<SpoutDX12>;
         unsafe fn new_spout_dx12_with_queue(device: *mut ID3D12Device, command_queue: *mut ID3D12CommandQueue) -> UniquePtr<SpoutDX12>;
@@ -242,4 +248,26 @@ impl SpoutDX12 {
     pub fn is_updated(&self) -> bool {
         self.inner.is_updated()
     }
+
+    pub fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+
+    pub fn get_sender_frame(&self) -> i64 {
+        self.inner.get_sender_frame()
+    }
+}
+
+pub fn get_sender_names() -> Vec<String> {
+    ffi::get_sender_names()
+}
+
+pub fn get_active_sender() -> Option<String> {
+    Some(ffi::get_active_sender()).filter(|name| !name.is_empty())
+}
+
+pub fn get_sender_host_path(name: impl AsRef<[u8]>) -> Option<String> {
+    let_cxx_string!(cxx_name = name);
+
+    Some(ffi::get_sender_host_path(&cxx_name)).filter(|path| !path.is_empty())
 }